@@ -150,7 +150,7 @@ fn bench_game_fool(b: &mut test::Bencher) {
     let mut ties = 0;
     let mut d_total_score: u32 = 0;
     let mut l_total_score: u32 = 0;
-    let mut game: Game<(), FoolPlayer, FoolPlayer> = Game::new(&d, &l);
+    let mut game: Game<(), &FoolPlayer, &FoolPlayer> = Game::new(&d, &l);
     b.iter(|| {
         game = Game::new(&d, &l);
         while game.get_current_state().is_some() {
@@ -182,7 +182,7 @@ fn bench_game_fool_vs_simple(b: &mut test::Bencher) {
     let mut ties = 0;
     let mut d_total_score: u32 = 0;
     let mut l_total_score: u32 = 0;
-    let mut game: Game<(), FoolPlayer, SimplePlayer> = Game::new(&d, &l);
+    let mut game: Game<(), &FoolPlayer, &SimplePlayer> = Game::new(&d, &l);
     b.iter(|| {
         game = Game::new(&d, &l);
         while game.get_current_state().is_some() {
@@ -214,7 +214,7 @@ fn bench_game_simple_vs_fool(b: &mut test::Bencher) {
     let mut ties = 0;
     let mut d_total_score: u32 = 0;
     let mut l_total_score: u32 = 0;
-    let mut game: Game<(), SimplePlayer, FoolPlayer> = Game::new(&d, &l);
+    let mut game: Game<(), &SimplePlayer, &FoolPlayer> = Game::new(&d, &l);
     b.iter(|| {
         game = Game::new(&d, &l);
         while game.get_current_state().is_some() {
@@ -246,7 +246,7 @@ fn bench_game_simple(b: &mut test::Bencher) {
     let mut ties = 0;
     let mut d_total_score: u32 = 0;
     let mut l_total_score: u32 = 0;
-    let mut game: Game<(), SimplePlayer, SimplePlayer> = Game::new(&d, &l);
+    let mut game: Game<(), &SimplePlayer, &SimplePlayer> = Game::new(&d, &l);
     b.iter(|| {
         game = Game::new(&d, &l);
         while game.get_current_state().is_some() {
@@ -0,0 +1,400 @@
+//! Implementation of matches between two players, with statistics on their results.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use board::*;
+use turn::*;
+use game::*;
+use clock::*;
+use eval::*;
+
+/// The smallest count of each of the five outcomes of a game pair (0, 1/4, 1/2, 3/4 or 1 point per game) when the SPRT
+/// estimates their distribution. As in fishtest (Stockfish's testing framework), which regularizes its pentanomial
+/// frequencies the same way, it keeps the variance from being zero when all pairs score the same.
+const SPRT_OUTCOME_REGULARIZATION: f64 = 0.001;
+
+/// How many game pairs a match played as an SPRT has to play before it can stop with a verdict. The normal
+/// approximation of the GSPRT only holds for enough pairs: a single pair would make its variance almost zero.
+const SPRT_MIN_PAIRS: usize = 10;
+
+/// A game played in a match, seen from the viewpoint of the match's first player.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// The side played by the first player.
+    pub side: ::Side,
+    /// All the moves of the game, opening included.
+    pub moves: Vec<Coord>,
+    /// The game's result.
+    pub result: GameResult,
+    /// The final disk count, as `(dark, light)`.
+    pub score: (u8, u8),
+}
+
+impl GameRecord {
+    /// Returns the points scored by the first player: 1 for a win, 0.5 for a draw, 0 for a loss.
+    pub fn get_points(&self) -> f64 {
+        match self.result.get_winner() {
+            Some(winner) if winner == self.side => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+
+    /// Returns the final disk count from the viewpoint of the first player, as `(own, opponent)`.
+    pub fn get_disks(&self) -> (u8, u8) {
+        match self.side {
+            ::Side::Dark => self.score,
+            ::Side::Light => (self.score.1, self.score.0),
+        }
+    }
+
+    /// Returns the game's moves in algebraic notation (e.g. `f5d6c3`).
+    pub fn get_transcript(&self) -> String {
+        self.moves.iter().map(|coord| coord.to_string()).collect()
+    }
+}
+
+/// Plays a game between two players, starting with the given opening moves.
+/// Illegal moves, and errors returned by the players, lose the game by forfeit.
+pub fn play_game<P, Q>(first: P, second: Q, side: ::Side, opening: &[Coord], time_control: Option<TimeControl>) -> GameRecord
+    where P: IsStatefulPlayer<()>, Q: IsStatefulPlayer<()>
+{
+    match side {
+        ::Side::Dark => record_game(Game::new(first, second), side, opening, time_control),
+        ::Side::Light => record_game(Game::new(second, first), side, opening, time_control),
+    }
+}
+
+/// Plays the given game to the end and records it.
+fn record_game<D, L>(mut game: Game<(), D, L>, side: ::Side, opening: &[Coord], time_control: Option<TimeControl>) -> GameRecord
+    where D: IsStatefulPlayer<()>, L: IsStatefulPlayer<()>
+{
+    game.set_illegal_move_policy(IllegalMovePolicy::Forfeit);
+    if let Some(time_control) = time_control {
+        game.set_time_controls(time_control, time_control);
+    }
+    for &coord in opening {
+        game.make_move(coord).expect("Openings are made of legal moves");
+    }
+    let mut forfeit = None;
+    while !game.is_endgame() {
+        let mover = game.get_current_state().expect("The game is running");
+        if game.play_turn().is_err() && !game.is_endgame() {
+            forfeit = Some(GameResult::WinByForfeit(mover.opposite()));
+            break;
+        }
+    }
+    GameRecord {
+        side,
+        moves: game.get_moves(),
+        result: forfeit.or_else(|| game.get_result()).expect("The game is over"),
+        score: game.get_current_score(),
+    }
+}
+
+/// A match between two players, which are created anew for each game by the given functions.
+/// Players alternate colours, and each opening (if any are given) is played once with each colour.
+pub struct Match<F, G> {
+    first: F,
+    second: G,
+    games: usize,
+    openings: Vec<Vec<Coord>>,
+    threads: usize,
+    time_control: Option<TimeControl>,
+}
+
+impl<F, G> Match<F, G> {
+    /// Creates a new match of the given number of games, played in a single thread from the first turn and untimed.
+    pub fn new(first: F, second: G, games: usize) -> Match<F, G> {
+        Match {
+            first,
+            second,
+            games,
+            openings: Vec::new(),
+            threads: 1,
+            time_control: None,
+        }
+    }
+
+    /// Sets the openings games start from, given as sequences of moves from the first turn.
+    /// Games go through the openings in order, each opening being played twice in a row with swapped colours.
+    pub fn set_openings(&mut self, openings: Vec<Vec<Coord>>) {
+        self.openings = openings;
+    }
+
+    /// Sets how many games are played in parallel.
+    #[inline(always)]
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Sets the time control of both players.
+    #[inline(always)]
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        self.time_control = Some(time_control);
+    }
+
+    /// Plays the match, returning its games in order.
+    pub fn play<P, Q>(&self) -> Vec<GameRecord>
+        where F: Fn() -> P + Sync, G: Fn() -> Q + Sync, P: IsStatefulPlayer<()>, Q: IsStatefulPlayer<()>
+    {
+        let next = AtomicUsize::new(0);
+        let records = Mutex::new(Vec::with_capacity(self.games));
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(self.games) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        if index >= self.games {
+                            break;
+                        }
+                        let record = self.play_game(index);
+                        records.lock().expect("No thread panicked while holding the lock").push((index, record));
+                    }
+                });
+            }
+        });
+        let mut records = records.into_inner().expect("No thread panicked while holding the lock");
+        records.sort_by_key(|&(index, _)| index);
+        records.into_iter().map(|(_, record)| record).collect()
+    }
+
+    /// Plays the match as a sequential probability ratio test: games are played in pairs, with the same opening
+    /// and swapped colours, until the test reaches a verdict (after `SPRT_MIN_PAIRS` pairs at least) or the match's
+    /// number of games has been played. The pairs in progress when the verdict is reached are still counted in the
+    /// report's games and log-likelihood ratio, but do not change its verdict.
+    pub fn play_sprt<P, Q>(&self, sprt: &Sprt) -> SprtReport
+        where F: Fn() -> P + Sync, G: Fn() -> Q + Sync, P: IsStatefulPlayer<()>, Q: IsStatefulPlayer<()>
+    {
+        let pairs = self.games / 2;
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let records = Mutex::new(Vec::with_capacity(self.games));
+        let verdict = Mutex::new(None);
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(pairs) {
+                scope.spawn(|| {
+                    while !stop.load(Ordering::SeqCst) {
+                        let pair = next.fetch_add(1, Ordering::SeqCst);
+                        if pair >= pairs {
+                            break;
+                        }
+                        let games = (self.play_game(2 * pair), self.play_game(2 * pair + 1));
+                        // Pairs started before the verdict are counted even if they finish after it, so that stopping
+                        // early does not favour the engine whose games end first.
+                        let mut records = records.lock().expect("No thread panicked while holding the lock");
+                        records.push((pair, games));
+                        let scores: Vec<f64> = records.iter().map(|(_, games)| pair_score(games)).collect();
+                        let mut verdict = verdict.lock().expect("No thread panicked while holding the lock");
+                        if verdict.is_none() && records.len() >= SPRT_MIN_PAIRS {
+                            *verdict = sprt.get_verdict(sprt.get_llr(&scores));
+                            stop.store(verdict.is_some(), Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
+        });
+        let mut records = records.into_inner().expect("No thread panicked while holding the lock");
+        records.sort_by_key(|&(pair, _)| pair);
+        let scores: Vec<f64> = records.iter().map(|(_, games)| pair_score(games)).collect();
+        SprtReport {
+            records: records.into_iter().flat_map(|(_, (first, second))| vec![first, second]).collect(),
+            llr: sprt.get_llr(&scores),
+            verdict: verdict.into_inner().expect("No thread panicked while holding the lock"),
+        }
+    }
+
+    /// Plays the game with the given index.
+    fn play_game<P, Q>(&self, index: usize) -> GameRecord
+        where F: Fn() -> P, G: Fn() -> Q, P: IsStatefulPlayer<()>, Q: IsStatefulPlayer<()>
+    {
+        let side = match index % 2 {
+            0 => ::Side::Dark,
+            _ => ::Side::Light,
+        };
+        let opening = if self.openings.is_empty() { &[][..] } else { &self.openings[(index / 2) % self.openings.len()][..] };
+        play_game((self.first)(), (self.second)(), side, opening, self.time_control)
+    }
+}
+
+/// The statistics of a match, from the viewpoint of its first player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The average final number of disks of the first player.
+    pub average_disks: f64,
+    /// The average final number of disks of the second player.
+    pub average_opponent_disks: f64,
+}
+
+impl MatchStats {
+    /// Computes the statistics of the given games.
+    pub fn new(records: &[GameRecord]) -> MatchStats {
+        let mut stats = MatchStats { wins: 0, draws: 0, losses: 0, average_disks: 0.0, average_opponent_disks: 0.0 };
+        for record in records {
+            match record.get_points() {
+                points if points > 0.5 => stats.wins += 1,
+                points if points < 0.5 => stats.losses += 1,
+                _ => stats.draws += 1,
+            }
+            let (own, opponent) = record.get_disks();
+            stats.average_disks += own as f64;
+            stats.average_opponent_disks += opponent as f64;
+        }
+        if !records.is_empty() {
+            stats.average_disks /= records.len() as f64;
+            stats.average_opponent_disks /= records.len() as f64;
+        }
+        stats
+    }
+
+    /// Returns the number of games.
+    #[inline(always)]
+    pub fn get_games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Returns the fraction of points scored by the first player.
+    pub fn get_score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.get_games() as f64
+    }
+
+    /// Returns the estimated Elo difference between the first and the second player.
+    /// It is infinite if one of the players scored every point.
+    pub fn get_elo_diff(&self) -> f64 {
+        elo_diff(self.get_score())
+    }
+
+    /// Returns the bounds of the confidence interval of the Elo difference, at about 95% confidence.
+    pub fn get_elo_interval(&self) -> (f64, f64) {
+        let games = self.get_games() as f64;
+        let score = self.get_score();
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2)) / games;
+        let margin = 1.96 * (variance / games).sqrt();
+        (elo_diff(score - margin), elo_diff(score + margin))
+    }
+}
+
+/// Converts an expected score into an Elo difference.
+pub fn elo_diff(score: f64) -> f64 {
+    let score = score.clamp(0.0, 1.0);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Converts an Elo difference into an expected score.
+pub fn expected_score(elo_diff: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo_diff / 400.0))
+}
+
+/// The average points scored by the first player in a pair of games.
+fn pair_score(games: &(GameRecord, GameRecord)) -> f64 {
+    (games.0.get_points() + games.1.get_points()) / 2.0
+}
+
+/// The hypothesis accepted by a sequential probability ratio test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SprtVerdict {
+    /// The Elo difference is (at most) the lower bound: the change is not an improvement.
+    H0,
+    /// The Elo difference is (at least) the upper bound: the change is an improvement.
+    H1,
+}
+
+/// A sequential probability ratio test between two hypotheses on the Elo difference between two players,
+/// H0 (the difference is `elo0`) and H1 (the difference is `elo1`), with error probabilities `alpha` and `beta`.
+/// The log-likelihood ratio is computed on the scores of game pairs with the normal approximation of the generalized SPRT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    elo0: f64,
+    elo1: f64,
+    alpha: f64,
+    beta: f64,
+}
+
+impl Sprt {
+    /// Creates a new test with the given Elo bounds and error probabilities.
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Sprt {
+        Sprt { elo0, elo1, alpha, beta }
+    }
+
+    /// Returns the bounds of the log-likelihood ratio: H0 is accepted below the lower one, H1 above the upper one.
+    pub fn get_bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Returns the log-likelihood ratio of H1 against H0, given the average scores of the game pairs played so far.
+    /// The mean and variance of the scores are those of the regularized distribution of the pairs' outcomes
+    /// (see `SPRT_OUTCOME_REGULARIZATION`).
+    pub fn get_llr(&self, pair_scores: &[f64]) -> f64 {
+        if pair_scores.is_empty() {
+            return 0.0;
+        }
+        let mut counts = [0.0f64; 5];
+        for &score in pair_scores {
+            counts[((score * 4.0).round() as usize).min(4)] += 1.0;
+        }
+        let counts: Vec<(f64, f64)> = counts.iter().enumerate()
+            .map(|(outcome, &count)| (outcome as f64 / 4.0, count.max(SPRT_OUTCOME_REGULARIZATION)))
+            .collect();
+        let pairs: f64 = counts.iter().map(|&(_, count)| count).sum();
+        let mean = counts.iter().map(|&(score, count)| score * count).sum::<f64>() / pairs;
+        let variance = counts.iter().map(|&(score, count)| (score - mean).powi(2) * count).sum::<f64>() / pairs;
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        pairs * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Returns the verdict corresponding to the given log-likelihood ratio, or `None` if the test has to go on.
+    pub fn get_verdict(&self, llr: f64) -> Option<SprtVerdict> {
+        let (lower, upper) = self.get_bounds();
+        if llr <= lower {
+            Some(SprtVerdict::H0)
+        } else if llr >= upper {
+            Some(SprtVerdict::H1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The outcome of a match played as a sequential probability ratio test.
+#[derive(Debug, Clone)]
+pub struct SprtReport {
+    /// The games played, pair by pair.
+    pub records: Vec<GameRecord>,
+    /// The final log-likelihood ratio.
+    pub llr: f64,
+    /// The verdict, or `None` if the match ended before the test reached one.
+    pub verdict: Option<SprtVerdict>,
+}
+
+/// Lists the openings of the given number of moves whose evaluation is at most `max_imbalance` away from zero,
+/// keeping only one opening per symmetry class of the resulting position.
+pub fn balanced_openings<E: IsEvaluator>(plies: usize, evaluator: &E, max_imbalance: f32) -> Vec<Vec<Coord>> {
+    let mut openings: Vec<(Vec<Coord>, Turn)> = vec![(Vec::new(), Turn::first_turn())];
+    for _ in 0..plies {
+        let mut next_openings: Vec<(Vec<Coord>, Turn)> = Vec::new();
+        let mut positions = HashSet::new();
+        for (moves, turn) in openings {
+            for coord in turn.get_legal_moves() {
+                let mut next_turn = turn;
+                next_turn.make_move(coord).expect("The move is legal");
+                if positions.insert(next_turn.canonical().0) {
+                    let mut next_moves = moves.clone();
+                    next_moves.push(coord);
+                    next_openings.push((next_moves, next_turn));
+                }
+            }
+        }
+        openings = next_openings;
+    }
+    openings.into_iter()
+        .filter(|(_, turn)| !turn.is_end_state() && evaluator.evaluate(turn).abs() <= max_imbalance)
+        .map(|(moves, _)| moves)
+        .collect()
+}
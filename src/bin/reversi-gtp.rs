@@ -0,0 +1,216 @@
+//! A front-end speaking a GTP-like text protocol on standard input and output, to drive the engine from scripts,
+//! referees and other GTP tooling.
+//!
+//! Usage: `reversi-gtp [--depth D] [--weights FILE]`
+//!
+//! Black is Dark and White is Light; moves are given in algebraic notation (e.g. `f5`), or as `pass`.
+//! Commands may be prefixed by a numeric id, which is repeated in the response. Supported commands are
+//! `protocol_version`, `name`, `version`, `known_command`, `list_commands`, `quit`, `boardsize`, `clear_board`,
+//! `komi` (ignored), `play`, `genmove`, `undo`, `showboard` and `final_score`.
+//! `undo` takes back moves until the side to move can play again, as `Game::undo` does.
+
+extern crate reversi;
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use reversi::board::*;
+use reversi::game::*;
+use reversi::eval::*;
+use reversi::search::*;
+
+const USAGE: &str = "Usage: reversi-gtp [--depth D] [--weights FILE]";
+
+/// The commands understood by the front-end.
+const COMMANDS: [&str; 14] = [
+    "protocol_version", "name", "version", "known_command", "list_commands", "quit", "boardsize", "clear_board",
+    "komi", "play", "genmove", "undo", "showboard", "final_score",
+];
+
+/// The game being played, without players: moves come from the protocol and the engine.
+type GtpGame = Game<(), (), ()>;
+
+/// The front-end's state: the game and the engine generating moves.
+struct Gtp {
+    game: GtpGame,
+    player: SearchPlayer<PatternEvaluator>,
+}
+
+impl Gtp {
+    /// Executes a command, returning its response or an error message, and whether the front-end has to quit.
+    fn execute(&mut self, command: &str, args: &[&str]) -> (Result<String, String>, bool) {
+        let response = match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok("reversi".to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => Ok(args.first().is_some_and(|name| COMMANDS.contains(name)).to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => return (Ok(String::new()), true),
+            "boardsize" => match args.first().and_then(|size| size.parse::<usize>().ok()) {
+                Some(BOARD_SIZE) => Ok(String::new()),
+                _ => Err("unacceptable size".to_string()),
+            },
+            "clear_board" => {
+                self.game = Game::new((), ());
+                Ok(String::new())
+            }
+            "komi" => Ok(String::new()),
+            "play" => self.play(args),
+            "genmove" => self.genmove(args),
+            "undo" => self.game.undo().map(|_| String::new()).map_err(|_| "cannot undo".to_string()),
+            "showboard" => Ok(self.showboard()),
+            "final_score" => Ok(self.final_score()),
+            _ => Err("unknown command".to_string()),
+        };
+        (response, false)
+    }
+
+    /// Plays a move (or a pass) for the given side: `play COLOR MOVE`.
+    fn play(&mut self, args: &[&str]) -> Result<String, String> {
+        let (side, vertex) = match *args {
+            [color, vertex] => (parse_color(color)?, vertex),
+            _ => return Err("syntax error".to_string()),
+        };
+        if vertex.eq_ignore_ascii_case("pass") {
+            // Passes are implied by turns: a side can only pass when it is not its turn.
+            return match self.game.get_current_state() {
+                Some(to_move) if to_move == side => Err("illegal move".to_string()),
+                _ => Ok(String::new()),
+            };
+        }
+        let coord: Coord = vertex.parse().map_err(|_| "invalid coordinate".to_string())?;
+        if self.game.get_current_state() != Some(side) {
+            return Err("illegal move".to_string());
+        }
+        self.game.make_move(coord).map(|_| String::new()).map_err(|_| "illegal move".to_string())
+    }
+
+    /// Has the engine play a move for the given side, which passes if it is not its turn: `genmove COLOR`.
+    fn genmove(&mut self, args: &[&str]) -> Result<String, String> {
+        let side = match *args {
+            [color] => parse_color(color)?,
+            _ => return Err("syntax error".to_string()),
+        };
+        if self.game.get_current_state() != Some(side) {
+            return Ok("pass".to_string());
+        }
+        let (coord, _) = self.player.search(self.game.get_current_turn()).expect("The game is running");
+        self.game.make_move(coord).expect("The engine plays legal moves");
+        Ok(coord.to_string())
+    }
+
+    /// Draws the board, with Black (Dark) disks as `X` and White (Light) disks as `O`, and the side to move.
+    fn showboard(&self) -> String {
+        let board = self.game.get_current_board();
+        let columns: String = (0..BOARD_SIZE).map(|col| format!(" {}", (b'a' + col as u8) as char)).collect();
+        let mut text = format!("\n  {}", columns);
+        for row in 0..BOARD_SIZE {
+            text.push_str(&format!("\n{} ", row + 1));
+            for col in 0..BOARD_SIZE {
+                let symbol = match board.get_cell(Coord::new(row, col)).expect("The coordinate is on the board") {
+                    Some(disk) if disk.get_side() == reversi::Side::Dark => 'X',
+                    Some(_) => 'O',
+                    None => '.',
+                };
+                text.push(' ');
+                text.push(symbol);
+            }
+        }
+        let (dark, light) = self.game.get_current_score();
+        let to_move = match self.game.get_current_state() {
+            Some(reversi::Side::Dark) => "black to move",
+            Some(reversi::Side::Light) => "white to move",
+            None => "game over",
+        };
+        text.push_str(&format!("\nblack {} - {} white, {}", dark, light, to_move));
+        text
+    }
+
+    /// Returns the score as `B+N`, `W+N` or `0`, counting the disks on the board. Once the game is over, empty
+    /// squares are awarded to the winner, as by WOF rules.
+    fn final_score(&self) -> String {
+        let (dark, light) = match self.game.get_result() {
+            Some(GameResult::WinByDisks(_, score)) => score,
+            _ => self.game.get_current_score(),
+        };
+        if dark > light {
+            format!("B+{}", dark - light)
+        } else if light > dark {
+            format!("W+{}", light - dark)
+        } else {
+            "0".to_string()
+        }
+    }
+}
+
+/// Parses a GTP color: Black is Dark and White is Light.
+fn parse_color(color: &str) -> Result<reversi::Side, String> {
+    match color.to_lowercase().as_str() {
+        "b" | "black" => Ok(reversi::Side::Dark),
+        "w" | "white" => Ok(reversi::Side::Light),
+        _ => Err("invalid color".to_string()),
+    }
+}
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut depth = 4;
+    let mut evaluator = PatternEvaluator::new(1);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--depth" => depth = value("--depth")?.parse().map_err(|_| "Invalid depth".to_string())?,
+            "--weights" => {
+                let path = value("--weights")?;
+                evaluator = PatternEvaluator::load(&path).map_err(|err| format!("Cannot load {}: {}", path, err))?;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let mut gtp = Gtp {
+        game: Game::new((), ()),
+        player: SearchPlayer::new(evaluator, depth),
+    };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|err| err.to_string())?;
+        // Comments and control characters are discarded, as GTP requires.
+        let line: String = line.split('#').next().unwrap_or("").chars().filter(|c| !c.is_control() || *c == '\t').collect();
+        let mut tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        let id = match tokens[0].parse::<u32>() {
+            Ok(id) => {
+                tokens.remove(0);
+                id.to_string()
+            }
+            Err(_) => String::new(),
+        };
+        let (response, quit) = match tokens.split_first() {
+            Some((command, args)) => gtp.execute(command, args),
+            None => (Err("missing command".to_string()), false),
+        };
+        let (status, text) = match response {
+            Ok(text) => ('=', text),
+            Err(text) => ('?', text),
+        };
+        let separator = if text.is_empty() || text.starts_with('\n') { "" } else { " " };
+        write!(output, "{}{}{}{}\n\n", status, id, separator, text).map_err(|err| err.to_string())?;
+        output.flush().map_err(|err| err.to_string())?;
+        if quit {
+            break;
+        }
+    }
+    Ok(())
+}
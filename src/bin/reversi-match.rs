@@ -0,0 +1,131 @@
+//! Plays a match between two engines and reports its statistics.
+//!
+//! Usage: `reversi-match FIRST SECOND [--games N] [--threads N] [--openings FILE] [--transcripts FILE] [--sprt ELO0 ELO1 ALPHA BETA]`
+//!
+//! Engines are given as `random`, `search:DEPTH[:WEIGHTS]` or `mcts:ITERATIONS`.
+//! Openings are read one per line in algebraic notation (e.g. `f5d6c3`).
+//! With `--sprt`, the match is played as a sequential probability ratio test, stopping as soon as it reaches a verdict;
+//! unless openings are given, games start from balanced 6-move openings.
+
+extern crate reversi;
+
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::process;
+use reversi::board::*;
+use reversi::eval::*;
+use reversi::arena::*;
+use reversi::engine::Engine;
+
+const USAGE: &str = "Usage: reversi-match FIRST SECOND [--games N] [--threads N] [--openings FILE] [--transcripts FILE] [--sprt ELO0 ELO1 ALPHA BETA]";
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut engines = Vec::new();
+    let mut games = 100;
+    let mut threads = 1;
+    let mut openings = None;
+    let mut transcripts = None;
+    let mut sprt = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--games" => games = value("--games")?.parse().map_err(|_| "Invalid number of games".to_string())?,
+            "--threads" => threads = value("--threads")?.parse().map_err(|_| "Invalid number of threads".to_string())?,
+            "--openings" => openings = Some(value("--openings")?),
+            "--transcripts" => transcripts = Some(value("--transcripts")?),
+            "--sprt" => {
+                let mut parameters = Vec::new();
+                for name in &["ELO0", "ELO1", "ALPHA", "BETA"] {
+                    parameters.push(value("--sprt")?.parse::<f64>().map_err(|_| format!("Invalid {}", name))?);
+                }
+                sprt = Some(Sprt::new(parameters[0], parameters[1], parameters[2], parameters[3]));
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => engines.push(arg),
+        }
+    }
+    if engines.len() != 2 {
+        return Err(USAGE.to_string());
+    }
+    for spec in &engines {
+        Engine::from_spec(spec).map_err(|err| err.to_string())?;
+    }
+
+    let first = || Engine::from_spec(&engines[0]).expect("The engine was checked");
+    let second = || Engine::from_spec(&engines[1]).expect("The engine was checked");
+    let mut game_match = Match::new(first, second, games);
+    game_match.set_threads(threads);
+    if let Some(path) = openings {
+        game_match.set_openings(read_openings(&path)?);
+    } else if sprt.is_some() {
+        game_match.set_openings(balanced_openings(6, &PatternEvaluator::new(1), 1.0));
+    }
+    let records = match sprt {
+        Some(sprt) => {
+            let report = game_match.play_sprt(&sprt);
+            let (lower, upper) = sprt.get_bounds();
+            println!("SPRT: LLR {:.3} [{:.3}, {:.3}], verdict {}", report.llr, lower, upper, match report.verdict {
+                Some(SprtVerdict::H0) => "H0 accepted",
+                Some(SprtVerdict::H1) => "H1 accepted",
+                None => "none",
+            });
+            report.records
+        }
+        None => game_match.play(),
+    };
+
+    if let Some(path) = transcripts {
+        let write = || -> std::io::Result<()> {
+            let mut writer = BufWriter::new(File::create(&path)?);
+            for record in &records {
+                let (dark, light) = record.score;
+                writeln!(writer, "{} {:?} {}-{} {}", record.get_transcript(), record.side, dark, light, result_notation(record))?;
+            }
+            writer.flush()
+        };
+        write().map_err(|err| format!("Cannot write {}: {}", path, err))?;
+    }
+    let stats = MatchStats::new(&records);
+    let (low, high) = stats.get_elo_interval();
+    println!("{} vs {}: {} games", engines[0], engines[1], stats.get_games());
+    println!("Wins {}, draws {}, losses {} (score {:.1}%)", stats.wins, stats.draws, stats.losses, 100.0 * stats.get_score());
+    println!("Average disks {:.2} - {:.2}", stats.average_disks, stats.average_opponent_disks);
+    println!("Elo difference {:.1} [{:.1}, {:.1}]", stats.get_elo_diff(), low, high);
+    Ok(())
+}
+
+/// Reads openings, one per line.
+fn read_openings(path: &str) -> Result<Vec<Vec<Coord>>, String> {
+    let file = File::open(path).map_err(|err| format!("Cannot open {}: {}", path, err))?;
+    let mut openings = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|err| format!("Cannot read {}: {}", path, err))?;
+        if !line.trim().is_empty() {
+            let moves = parse_coords(&line).map_err(|_| format!("Invalid opening {}", line))?;
+            let mut turn = reversi::turn::Turn::first_turn();
+            for &coord in &moves {
+                turn.make_move(coord).map_err(|_| format!("Illegal opening {}", line))?;
+            }
+            openings.push(moves);
+        }
+    }
+    Ok(openings)
+}
+
+/// Returns the result from the first engine's viewpoint: `1`, `1/2` or `0`.
+fn result_notation(record: &GameRecord) -> &'static str {
+    match record.get_points() {
+        points if points > 0.5 => "1",
+        points if points < 0.5 => "0",
+        _ => "1/2",
+    }
+}
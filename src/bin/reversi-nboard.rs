@@ -0,0 +1,183 @@
+//! An engine speaking the NBoard protocol on standard input and output, to be plugged into NBoard and compatible GUIs.
+//!
+//! Usage: `reversi-nboard [--weights FILE] [--book FILE]`
+//!
+//! The engine searches with a pattern evaluator (positional by default, or loaded from a weights file)
+//! to the depth set by the GUI, and plays from an opening book, if given, as long as the game is in book.
+
+extern crate rand;
+extern crate reversi;
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::time::Instant;
+use reversi::board::*;
+use reversi::turn::*;
+use reversi::eval::*;
+use reversi::search::*;
+use reversi::book::*;
+use reversi::ggf::*;
+
+const USAGE: &str = "Usage: reversi-nboard [--weights FILE] [--book FILE]";
+
+/// The engine's name, as reported to the GUI.
+const NAME: &str = "reversi";
+
+/// The search depth used until the GUI sets one.
+const DEFAULT_DEPTH: u8 = 6;
+
+/// The engine's state: the current position and the side to move as seen by the GUI,
+/// which differs from the turn's when the side to move has to pass.
+struct Engine {
+    player: SearchPlayer<PatternEvaluator>,
+    book: Option<Book>,
+    turn: Turn,
+    side: reversi::Side,
+}
+
+impl Engine {
+    /// Handles a command, writing the replies to the given output.
+    /// It returns `false` when the engine has to quit.
+    fn handle<W: Write>(&mut self, command: &str, output: &mut W) -> io::Result<bool> {
+        let (name, argument) = match command.find(char::is_whitespace) {
+            Some(index) => (&command[..index], command[index..].trim()),
+            None => (command, ""),
+        };
+        match name {
+            "nboard" => writeln!(output, "set myname {}", NAME)?,
+            "set" => self.set(argument, output)?,
+            "move" => match parse_move(argument) {
+                Ok(Some(coord)) if self.turn.get_state() == Some(self.side) && self.turn.make_move(coord).is_ok() => {
+                    self.side = self.side.opposite();
+                }
+                // Passes are only legal when the side to move has no legal moves.
+                Ok(None) if self.turn.get_state() != Some(self.side) => self.side = self.side.opposite(),
+                _ => writeln!(output, "status Illegal move {}", argument)?,
+            },
+            "go" => self.go(output)?,
+            "hint" => self.hint(argument.parse().unwrap_or(1), output)?,
+            "ping" => writeln!(output, "pong {}", argument)?,
+            "learn" => writeln!(output, "learned")?,
+            "quit" => return Ok(false),
+            _ => {}
+        }
+        output.flush()?;
+        Ok(true)
+    }
+
+    /// Handles the `set` commands: `set game GGF`, `set depth N` and `set contempt N` (which is ignored).
+    /// Invalid games are reported with a status, and leave the current position unchanged.
+    fn set<W: Write>(&mut self, argument: &str, output: &mut W) -> io::Result<()> {
+        let (name, value) = match argument.find(char::is_whitespace) {
+            Some(index) => (&argument[..index], argument[index..].trim()),
+            None => (argument, ""),
+        };
+        match name {
+            "game" => match parse_game(value) {
+                Ok(game) => match game.get_current_turn() {
+                    Ok(turn) => {
+                        self.turn = turn;
+                        self.side = game.get_side_to_move();
+                    }
+                    Err(_) => writeln!(output, "status Illegal moves in game")?,
+                },
+                Err(_) => writeln!(output, "status Invalid game")?,
+            },
+            "depth" => {
+                if let Ok(depth) = value.parse() {
+                    self.player = SearchPlayer::new(self.player.get_evaluator().clone(), depth);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Plays a move for the side to move, and replies with it, its evaluation and the time it took.
+    fn go<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+        let start = Instant::now();
+        if self.turn.get_state() != Some(self.side) {
+            self.side = self.side.opposite();
+            return writeln!(output, "=== PA");
+        }
+        let book_move = self.book.as_ref().and_then(|book| book.choose_move(&self.turn, &mut rand::thread_rng()));
+        let (coord, eval) = match book_move {
+            Some(coord) => (coord, 0.0),
+            None => {
+                let (coord, score) = self.player.search(&self.turn).expect("The turn is running");
+                (coord, self.to_mover(score))
+            }
+        };
+        self.turn.make_move(coord).expect("The engine plays legal moves");
+        self.side = self.side.opposite();
+        writeln!(output, "=== {}/{:.2}/{:.3}", coord.to_string().to_uppercase(), eval, start.elapsed().as_secs_f64())
+    }
+
+    /// Replies with the best moves for the side to move, up to the given number, and their evaluations.
+    fn hint<W: Write>(&self, count: usize, output: &mut W) -> io::Result<()> {
+        writeln!(output, "status Analyzing")?;
+        if self.turn.get_state() == Some(self.side) {
+            let mut moves: Vec<(Coord, f32)> = self.player.score_moves(&self.turn).into_iter()
+                .map(|(coord, score)| (coord, self.to_mover(score)))
+                .collect();
+            moves.sort_by(|a, b| b.1.partial_cmp(&a.1).expect("Scores are never NaN"));
+            for (coord, eval) in moves.into_iter().take(count) {
+                writeln!(output, "search {} {:.2} 0 {}", coord.to_string().to_uppercase(), eval, self.player.get_depth())?;
+            }
+        }
+        writeln!(output, "status")
+    }
+
+    /// Converts a score (positive when Light is winning) to the viewpoint of the side to move.
+    fn to_mover(&self, score: f32) -> f32 {
+        match self.side {
+            reversi::Side::Light => score,
+            reversi::Side::Dark => -score,
+        }
+    }
+}
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut evaluator = PatternEvaluator::new(1);
+    let mut book = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--weights" => {
+                let path = value("--weights")?;
+                evaluator = PatternEvaluator::load(&path).map_err(|err| format!("Cannot load {}: {}", path, err))?;
+            }
+            "--book" => {
+                let path = value("--book")?;
+                book = Some(Book::load(&path).map_err(|err| format!("Cannot load {}: {}", path, err))?);
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let mut engine = Engine {
+        player: SearchPlayer::new(evaluator, DEFAULT_DEPTH),
+        book,
+        turn: Turn::first_turn(),
+        side: reversi::Side::Dark,
+    };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|err| err.to_string())?;
+        if !engine.handle(line.trim(), &mut output).map_err(|err| err.to_string())? {
+            break;
+        }
+    }
+    Ok(())
+}
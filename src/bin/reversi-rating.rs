@@ -0,0 +1,71 @@
+//! Updates the Elo and Glicko-2 ratings stored in a ratings file with the games of a games file, and prints them.
+//!
+//! Usage: `reversi-rating RATINGS GAMES [--disks] [--k-factor K] [--tau T]`
+//!
+//! Games are read one per line, with tab-separated Dark player, Light player and final disk count (e.g. `40-24`),
+//! and make up a single Glicko-2 rating period. Games files starting with `(;` are read as GGF instead, as saved
+//! by NBoard and game servers. The ratings file is created if it does not exist.
+
+extern crate reversi;
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::process;
+use reversi::rating::*;
+
+const USAGE: &str = "Usage: reversi-rating RATINGS GAMES [--disks] [--k-factor K] [--tau T]";
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut paths = Vec::new();
+    let mut mode = ScoreMode::Result;
+    let mut k_factor = None;
+    let mut tau = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--disks" => mode = ScoreMode::Disks,
+            "--k-factor" => k_factor = Some(value("--k-factor")?.parse().map_err(|_| "Invalid K-factor".to_string())?),
+            "--tau" => tau = Some(value("--tau")?.parse().map_err(|_| "Invalid system constant".to_string())?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() != 2 {
+        return Err(USAGE.to_string());
+    }
+
+    let mut ratings = match Ratings::load(&paths[0]) {
+        Ok(ratings) => ratings,
+        Err(ref err) if err.kind() == ErrorKind::NotFound => Ratings::new(),
+        Err(err) => return Err(format!("Cannot load {}: {}", paths[0], err)),
+    };
+    if let Some(k_factor) = k_factor {
+        ratings.set_k_factor(k_factor);
+    }
+    if let Some(tau) = tau {
+        ratings.set_tau(tau);
+    }
+    let games = fs::read_to_string(&paths[1])
+        .and_then(|text| if text.trim_start().starts_with("(;") { read_ggf_games(&text) } else { read_games(text.as_bytes()) })
+        .map_err(|err| format!("Cannot load {}: {}", paths[1], err))?;
+    ratings.update_elo(&games, mode);
+    ratings.update_glicko(&games, mode);
+    ratings.save(&paths[0]).map_err(|err| format!("Cannot save {}: {}", paths[0], err))?;
+
+    let mut players = ratings.get_players();
+    players.sort_by(|a, b| b.1.elo.partial_cmp(&a.1.elo).expect("Ratings are never NaN"));
+    println!("{:<20} {:>7} {:>7} {:>6}", "Player", "Elo", "Glicko", "RD");
+    for (name, rating) in players {
+        println!("{:<20} {:>7.1} {:>7.1} {:>6.1}", name, rating.elo, rating.glicko, rating.deviation);
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Plays games between processes over TCP: serves an engine as a remote player, or hosts a game
+//! where either side can be a remote player (see the `remote` module for the protocol).
+//!
+//! Usage:
+//! `reversi-remote serve ADDRESS [--engine ENGINE]`
+//! `reversi-remote host DARK LIGHT [--timeout SECONDS] [--time SECONDS]`
+//!
+//! Engines are given as `easy`, `medium`, `hard`, `random`, `search:DEPTH[:WEIGHTS]` or `mcts:ITERATIONS`
+//! (`medium` by default). When hosting, each side is either an engine or `tcp:ADDRESS`, a remote player to connect to,
+//! which has to answer within the timeout (10 seconds by default). With `--time`, both sides get the given time.
+
+extern crate reversi;
+
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::thread;
+use std::time::Duration;
+use reversi::game::*;
+use reversi::clock::*;
+use reversi::remote::*;
+use reversi::engine::Engine;
+
+const USAGE: &str = "Usage: reversi-remote serve ADDRESS [--engine ENGINE]\n       reversi-remote host DARK LIGHT [--timeout SECONDS] [--time SECONDS]";
+
+/// How long to wait before accepting connections again after failing to, e.g. when out of file descriptors.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// A player taking part in a hosted game.
+enum HostPlayer {
+    Local(Engine),
+    Remote(RemotePlayer),
+}
+
+impl HostPlayer {
+    /// Creates a player from its description: an engine, or `tcp:ADDRESS` for a remote player.
+    fn from_spec(spec: &str, timeout: Duration) -> Result<HostPlayer, String> {
+        match spec.strip_prefix("tcp:") {
+            Some(address) => RemotePlayer::connect(address, timeout)
+                .map(HostPlayer::Remote)
+                .map_err(|err| format!("Cannot connect to {}: {}", address, err)),
+            None => Engine::from_spec(spec).map(HostPlayer::Local).map_err(|err| err.to_string()),
+        }
+    }
+
+    /// Returns the player as a stateful player.
+    fn as_player(&mut self) -> &mut dyn IsStatefulPlayer<()> {
+        match *self {
+            HostPlayer::Local(ref mut engine) => engine,
+            HostPlayer::Remote(ref mut remote) => remote,
+        }
+    }
+}
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut engine = "medium".to_string();
+    let mut timeout = Duration::from_secs(10);
+    let mut time_control = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        // Infinite or huge values cannot make a duration.
+        let mut seconds = |name: &str| value(name)?.parse::<f64>().ok().filter(|&seconds| seconds.is_finite() && seconds > 0.0)
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()).ok_or_else(|| format!("Invalid value for {}", name));
+        match arg.as_str() {
+            "--engine" => engine = value("--engine")?,
+            "--timeout" => timeout = seconds("--timeout")?,
+            "--time" => time_control = Some(TimeControl::SuddenDeath(seconds("--time")?)),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => positional.push(arg),
+        }
+    }
+
+    match positional.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["serve", address] => serve(address, &engine),
+        ["host", dark, light] => host(dark, light, timeout, time_control),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Serves the given engine to every host connecting to the address, each game on its own thread.
+/// Connections which cannot be accepted are reported, and serving goes on.
+fn serve(address: &str, engine: &str) -> Result<(), String> {
+    Engine::from_spec(engine).map_err(|err| err.to_string())?;
+    let listener = TcpListener::bind(address).map_err(|err| format!("Cannot listen on {}: {}", address, err))?;
+    eprintln!("Serving {} on {}", engine, address);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Cannot accept a connection: {}", err);
+                thread::sleep(ACCEPT_RETRY_DELAY);
+                continue;
+            }
+        };
+        let engine = Engine::from_spec(engine).expect("The engine was checked");
+        thread::spawn(move || {
+            if let Err(err) = serve_player::<(), _>(engine, stream) {
+                eprintln!("Remote game failed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Hosts a game between the given players, printing its moves and result.
+fn host(dark: &str, light: &str, timeout: Duration, time_control: Option<TimeControl>) -> Result<(), String> {
+    let mut dark = HostPlayer::from_spec(dark, timeout)?;
+    let mut light = HostPlayer::from_spec(light, timeout)?;
+    let mut game = Game::new(dark.as_player(), light.as_player());
+    game.set_illegal_move_policy(IllegalMovePolicy::Forfeit);
+    if let Some(time_control) = time_control {
+        game.set_time_controls(time_control, time_control);
+    }
+    while !game.is_endgame() {
+        let side = game.get_current_state().expect("The game is running");
+        match game.play_turn() {
+            Ok(PlayerAction::Move(coord)) => println!("{:?} plays {}", side, coord),
+            Ok(_) => {}
+            Err(err) if game.is_endgame() => println!("{}", err),
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+    let (dark_disks, light_disks) = game.get_current_score();
+    println!("Result: {:?} ({}-{})", game.get_result().expect("The game is over"), dark_disks, light_disks);
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Plays self-play games between two engines and writes every position to a dataset file.
+//!
+//! Usage: `reversi-selfplay OUTPUT [--games N] [--dark ENGINE] [--light ENGINE] [--noise-plies K] [--temperature T] [--seed N] [--csv]`
+//!
+//! Engines are given as `random`, `search:DEPTH[:WEIGHTS]` or `mcts:ITERATIONS` (default `search:3`).
+
+extern crate reversi;
+
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+use reversi::selfplay::*;
+
+const USAGE: &str = "Usage: reversi-selfplay OUTPUT [--games N] [--dark ENGINE] [--light ENGINE] [--noise-plies K] [--temperature T] [--seed N] [--csv]";
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut paths = Vec::new();
+    let mut games = 100;
+    let mut dark = "search:3".to_string();
+    let mut light = "search:3".to_string();
+    let mut noise_plies = 0;
+    let mut temperature = 1.0;
+    let mut seed = None;
+    let mut format = RecordFormat::Binary;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--games" => games = value("--games")?.parse().map_err(|_| "Invalid number of games".to_string())?,
+            "--dark" => dark = value("--dark")?,
+            "--light" => light = value("--light")?,
+            "--noise-plies" => noise_plies = value("--noise-plies")?.parse().map_err(|_| "Invalid number of plies".to_string())?,
+            "--temperature" => temperature = value("--temperature")?.parse().map_err(|_| "Invalid temperature".to_string())?,
+            "--seed" => seed = Some(value("--seed")?.parse::<u32>().map_err(|_| "Invalid seed".to_string())?),
+            "--csv" => format = RecordFormat::Csv,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() != 1 {
+        return Err(USAGE.to_string());
+    }
+
+    let dark = Engine::from_spec(&dark).map_err(|err| err.to_string())?;
+    let light = Engine::from_spec(&light).map_err(|err| err.to_string())?;
+    let mut self_play = SelfPlay::new(dark, light);
+    self_play.set_noise_plies(noise_plies);
+    self_play.set_temperature(temperature);
+    if let Some(seed) = seed {
+        // XorShift generators cannot be seeded with zeros only.
+        self_play.set_seed([seed, 0x9E37_79B9, 0x7F4A_7C15, 1]);
+    }
+    let file = File::create(&paths[0]).map_err(|err| format!("Cannot create {}: {}", paths[0], err))?;
+    let mut writer = RecordWriter::new(BufWriter::new(file), format).map_err(|err| err.to_string())?;
+    let mut positions = 0;
+    for game in 0..games {
+        let records = self_play.play_game();
+        positions += records.len();
+        writer.write(&records).map_err(|err| format!("Cannot write {}: {}", paths[0], err))?;
+        println!("Game {}: result {}", game + 1, records.last().map_or(0, |record| record.result));
+    }
+    writer.into_inner().map_err(|err| format!("Cannot write {}: {}", paths[0], err))?;
+    println!("Wrote {} positions from {} games", positions, games);
+    Ok(())
+}
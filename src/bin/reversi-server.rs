@@ -0,0 +1,41 @@
+//! Serves games over HTTP with a JSON API, for web frontends (see the `server` module for the endpoints).
+//!
+//! Usage: `reversi-server [--address ADDRESS] [--engine ENGINE]`
+//!
+//! The server listens on `127.0.0.1:8080` by default. Engine moves are played by the given engine
+//! (`easy`, `medium`, `hard`, `random`, `search:DEPTH[:WEIGHTS]` or `mcts:ITERATIONS`), `medium` by default.
+
+extern crate reversi;
+
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use reversi::server::*;
+
+const USAGE: &str = "Usage: reversi-server [--address ADDRESS] [--engine ENGINE]";
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut address = "127.0.0.1:8080".to_string();
+    let mut server = Server::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--address" => address = value("--address")?,
+            "--engine" => server.set_engine(&value("--engine")?).map_err(|err| err.to_string())?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let listener = TcpListener::bind(&address).map_err(|err| format!("Cannot listen on {}: {}", address, err))?;
+    eprintln!("Listening on http://{}", address);
+    server.serve(listener);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Trains a pattern-based evaluator on a dataset of scored positions, and writes its weights file.
+//! The evaluator either starts from scratch with the given number of stages, or from the weights of another one.
+//!
+//! Usage: `reversi-train DATASET WEIGHTS [--stages N | --init WEIGHTS] [--epochs N] [--learning-rate X]`
+
+extern crate reversi;
+
+use std::env;
+use std::process;
+use reversi::eval::*;
+use reversi::train::*;
+
+const USAGE: &str = "Usage: reversi-train DATASET WEIGHTS [--stages N | --init WEIGHTS] [--epochs N] [--learning-rate X]";
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut paths = Vec::new();
+    let mut stages = None;
+    let mut init = None;
+    let mut trainer = Trainer::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--stages" => stages = Some(value("--stages")?.parse().map_err(|_| "Invalid number of stages".to_string())?),
+            "--epochs" => trainer.set_epochs(value("--epochs")?.parse().map_err(|_| "Invalid number of epochs".to_string())?),
+            "--learning-rate" => trainer.set_learning_rate(value("--learning-rate")?.parse().map_err(|_| "Invalid learning rate".to_string())?),
+            "--init" => init = Some(value("--init")?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() != 2 {
+        return Err(USAGE.to_string());
+    }
+    if stages == Some(0) {
+        return Err("There has to be at least one stage".to_string());
+    }
+    if stages.is_some() && init.is_some() {
+        return Err("The number of stages is given by the initial weights, --stages cannot be used with --init".to_string());
+    }
+
+    let samples = load_dataset(&paths[0]).map_err(|err| format!("Cannot load {}: {}", paths[0], err))?;
+    let mut evaluator = match init {
+        Some(path) => PatternEvaluator::load(&path).map_err(|err| format!("Cannot load {}: {}", path, err))?,
+        None => PatternEvaluator::new(stages.unwrap_or(1)),
+    };
+    println!("Training on {} samples, initial error {}", samples.len(), mean_squared_error(&evaluator, &samples));
+    for (epoch, error) in trainer.train(&mut evaluator, &samples).into_iter().enumerate() {
+        println!("Epoch {}: error {}", epoch + 1, error);
+    }
+    evaluator.save(&paths[1]).map_err(|err| format!("Cannot save {}: {}", paths[1], err))
+}
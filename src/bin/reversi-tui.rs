@@ -0,0 +1,604 @@
+//! Plays and reviews Reversi games in a full-screen terminal interface.
+//!
+//! Usage: `reversi-tui [--dark PLAYER] [--light PLAYER] [--time MINUTES[+SECONDS]] [--load FILE]`
+//!
+//! Players are `human` or an engine (`easy`, `medium`, `hard`, `random`, `search:DEPTH[:WEIGHTS]` or
+//! `mcts:ITERATIONS`); by default, a human plays Dark against a `medium` engine. With `--time`, both sides get
+//! the given minutes, plus the given seconds per move. A game loaded from a transcript file (e.g. `f5d6c3`)
+//! starts from its first move, to be stepped through.
+//!
+//! Keys: arrows (or `hjkl`) move the cursor, enter or space plays the move under it, `u` undoes moves until
+//! the side to move can play again, `[` and `]` step one move back and forward, and `q` quits.
+//! Engines search in the background, so that keys keep working while they think. Engines do not play and clocks
+//! do not run while there are moves to step forward through; stepping back restores the clocks as they were at
+//! that move.
+
+extern crate rand;
+extern crate reversi;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::{self, Command, Stdio};
+use std::mem;
+use std::sync::mpsc::{self, Receiver, RecvError, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use reversi::board::*;
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::clock::*;
+use reversi::engine::{Engine, Player};
+
+const USAGE: &str = "Usage: reversi-tui [--dark PLAYER] [--light PLAYER] [--time MINUTES[+SECONDS]] [--load FILE]";
+
+/// The engine evaluating the positions shown, in the background.
+const EVAL_ENGINE: &str = "search:4";
+
+/// How often the screen is redrawn while waiting for keys, so that clocks keep ticking.
+const REFRESH: Duration = Duration::from_millis(200);
+
+/// The longest time or increment of a time control, in seconds (a day).
+const MAX_TIME: f64 = 86_400.0;
+
+/// How long to wait for the rest of an escape sequence.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// The screen column where the side panel starts.
+const PANEL_COLUMN: usize = 32;
+
+/// How many lines of the move list fit in the side panel.
+const MOVE_LIST_ROWS: usize = 10;
+
+/// The game being played, without players: moves come from the keyboard and the engines.
+type TuiGame = Game<(), (), ()>;
+
+/// The events the interface responds to, sent by the threads reading keys, searching and evaluating.
+enum Event {
+    Key(Key),
+    /// Standard input was closed.
+    Closed,
+    /// The engine which was searching, given back with the move it found.
+    EngineMove(Engine, Option<Coord>),
+    /// The evaluation of a position, for the side panel.
+    Eval(Turn, Option<f32>),
+}
+
+/// The keys the interface responds to.
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Char(char),
+}
+
+/// Keeps the terminal in raw mode while alive, if standard input is a terminal.
+struct RawMode {
+    saved: Option<String>,
+}
+
+impl RawMode {
+    /// Saves the terminal's settings and switches it to raw mode.
+    fn enable() -> RawMode {
+        let saved = stty(&["-g"]);
+        if saved.is_some() {
+            stty(&["raw", "-echo"]);
+        }
+        RawMode { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(ref saved) = self.saved {
+            stty(&[saved.trim()]);
+        }
+    }
+}
+
+/// Runs `stty` on the terminal with the given arguments, returning its output if it succeeds.
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).stderr(Stdio::null()).output().ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+/// Reads standard input on a separate thread, which sends bytes to another one turning them into key events,
+/// so that the screen can be redrawn while waiting for keys.
+fn spawn_reader(events: Sender<Event>) {
+    let (sender, bytes) = mpsc::channel();
+    thread::spawn(move || {
+        for byte in io::stdin().lock().bytes() {
+            match byte {
+                Ok(byte) if sender.send(byte).is_ok() => {}
+                _ => break,
+            }
+        }
+    });
+    thread::spawn(move || loop {
+        let event = match read_key(&bytes) {
+            Ok(Some(key)) => Event::Key(key),
+            Ok(None) => continue,
+            Err(_) => Event::Closed,
+        };
+        let closed = matches!(event, Event::Closed);
+        if events.send(event).is_err() || closed {
+            break;
+        }
+    });
+}
+
+/// Waits for a key. It returns an error once standard input is closed.
+fn read_key(bytes: &Receiver<u8>) -> Result<Option<Key>, RecvError> {
+    let key = match bytes.recv()? {
+        // Arrows are sent as `ESC [ A` to `ESC [ D`.
+        0x1b => match (bytes.recv_timeout(ESCAPE_TIMEOUT), bytes.recv_timeout(ESCAPE_TIMEOUT)) {
+            (Ok(b'['), Ok(b'A')) => Key::Up,
+            (Ok(b'['), Ok(b'B')) => Key::Down,
+            (Ok(b'['), Ok(b'C')) => Key::Right,
+            (Ok(b'['), Ok(b'D')) => Key::Left,
+            _ => return Ok(None),
+        },
+        b'\r' | b'\n' | b' ' => Key::Enter,
+        // Ctrl-C, which raw mode does not turn into a signal.
+        0x03 => Key::Char('q'),
+        byte => Key::Char(byte as char),
+    };
+    Ok(Some(key))
+}
+
+/// The interface's state: the game, its players and what is shown of it.
+struct Tui {
+    game: TuiGame,
+    /// The game as it was at the start of each move reached, by number of moves played, to step back to.
+    history: Vec<TuiGame>,
+    dark: Player,
+    light: Player,
+    move_start: Instant,
+    /// The moves which can be stepped forward through, next one last.
+    future: Vec<Coord>,
+    cursor: Coord,
+    /// The side whose engine is searching, with the moves played when it started.
+    thinking: Option<(reversi::Side, Vec<Coord>)>,
+    /// Where the searching and evaluating threads send their results.
+    events: Sender<Event>,
+    /// The positions to evaluate, sent to the evaluation thread.
+    eval_requests: Sender<Turn>,
+    eval: Option<f32>,
+    message: String,
+}
+
+impl Tui {
+    /// Returns the side to move, or `None` if the game is over (on time included).
+    fn get_running_side(&self) -> Option<reversi::Side> {
+        if self.game.is_endgame() {
+            None
+        } else {
+            self.game.get_current_state()
+        }
+    }
+
+    /// Returns the player of the given side.
+    fn get_player(&self, side: reversi::Side) -> &Player {
+        match side {
+            reversi::Side::Dark => &self.dark,
+            reversi::Side::Light => &self.light,
+        }
+    }
+
+    /// Returns the time left to the given side, counting the time spent on the current move.
+    fn get_time_left(&self, side: reversi::Side) -> Option<Duration> {
+        let time_left = self.game.get_clock(side)?.get_time_left();
+        match self.get_running_side() {
+            Some(running) if running == side && self.future.is_empty() => {
+                Some(time_left.checked_sub(self.move_start.elapsed()).unwrap_or_default())
+            }
+            _ => Some(time_left),
+        }
+    }
+
+    /// Ends the game if the side to move has run out of time while thinking.
+    fn check_time(&mut self) {
+        if let Some(side) = self.get_running_side() {
+            if self.get_time_left(side) == Some(Duration::from_secs(0)) {
+                // The error only reports the timeout, which the game's result already records.
+                let _ = self.game.charge_clock(side, self.move_start.elapsed());
+            }
+        }
+    }
+
+    /// Plays a move for the side to move, charging its clock unless it steps forward through known moves,
+    /// and updates what is shown.
+    fn play(&mut self, side: reversi::Side, coord: Coord) {
+        let ply = self.game.get_moves().len();
+        if self.future.last() == Some(&coord) {
+            self.future.pop();
+        } else {
+            if self.future.is_empty() && self.game.charge_clock(side, self.move_start.elapsed()).is_err() {
+                return self.on_position_change();
+            }
+            self.future.clear();
+            self.history.truncate(ply + 1);
+        }
+        match self.history.get(ply + 1).cloned() {
+            Some(game) => self.game = game,
+            None => {
+                self.game.make_move(coord).expect("Only legal moves are played");
+                self.history.push(self.game.clone());
+            }
+        }
+        self.on_position_change();
+    }
+
+    /// Starts the search of the engine to move on its own thread, if it is its turn and there are no moves
+    /// to step forward through. The engine is given back with its move in an `Event::EngineMove`.
+    fn start_engine(&mut self) {
+        let side = match self.get_running_side() {
+            Some(side) if self.future.is_empty() && self.thinking.is_none() => side,
+            _ => return,
+        };
+        let player = match side {
+            reversi::Side::Dark => &mut self.dark,
+            reversi::Side::Light => &mut self.light,
+        };
+        let mut engine = match *player {
+            Player::Human => return,
+            // A random engine stands in for the searching one until it is given back.
+            Player::Engine(_, ref mut engine) => mem::replace(engine, Engine::Random),
+        };
+        let turn = *self.game.get_current_turn();
+        let events = self.events.clone();
+        thread::spawn(move || {
+            let coord = engine.choose_move(&turn, 0.0, &mut rand::thread_rng()).map(|(coord, _)| coord);
+            // The interface may have quit in the meantime.
+            let _ = events.send(Event::EngineMove(engine, coord));
+        });
+        self.thinking = Some((side, self.game.get_moves()));
+    }
+
+    /// Gives the engine back to its player and plays its move, unless the game has changed during the search.
+    fn on_engine_move(&mut self, engine: Engine, coord: Option<Coord>) {
+        let (side, moves) = self.thinking.take().expect("An engine was searching");
+        let player = match side {
+            reversi::Side::Dark => &mut self.dark,
+            reversi::Side::Light => &mut self.light,
+        };
+        if let Player::Engine(_, ref mut searching) = *player {
+            *searching = engine;
+        }
+        if self.get_running_side() == Some(side) && self.future.is_empty() && self.game.get_moves() == moves {
+            self.play(side, coord.expect("The game is running"));
+        }
+    }
+
+    /// Handles a key, returning `false` when the interface has to quit.
+    fn handle(&mut self, key: Key) -> bool {
+        let (row, col) = (self.cursor.get_row(), self.cursor.get_col());
+        self.message.clear();
+        match key {
+            Key::Up | Key::Char('k') => self.cursor = Coord::new(row.saturating_sub(1), col),
+            Key::Down | Key::Char('j') => self.cursor = Coord::new((row + 1).min(BOARD_SIZE - 1), col),
+            Key::Left | Key::Char('h') => self.cursor = Coord::new(row, col.saturating_sub(1)),
+            Key::Right | Key::Char('l') => self.cursor = Coord::new(row, (col + 1).min(BOARD_SIZE - 1)),
+            Key::Enter => match self.get_running_side() {
+                Some(side) if matches!(*self.get_player(side), Player::Human) => {
+                    if self.game.get_current_turn().check_move(self.cursor).is_ok() {
+                        let cursor = self.cursor;
+                        self.play(side, cursor);
+                    } else {
+                        self.message = format!("Illegal move {}", self.cursor);
+                    }
+                }
+                Some(_) => self.message = "It is not your turn".to_string(),
+                None => self.message = "The game is over".to_string(),
+            },
+            Key::Char('u') => {
+                let moves = self.game.get_moves();
+                // Undoing from the start of the current move also takes back a loss on time during it.
+                let mut game = self.history[moves.len()].clone();
+                match game.undo() {
+                    Ok(()) => {
+                        let ply = game.get_moves().len();
+                        self.future.extend(moves[ply..].iter().rev());
+                        self.game = self.history[ply].clone();
+                        self.on_position_change();
+                    }
+                    Err(_) => self.message = "Nothing to undo".to_string(),
+                }
+            }
+            Key::Char('[') => {
+                let mut moves = self.game.get_moves();
+                match moves.pop() {
+                    Some(coord) => {
+                        self.game = self.history[moves.len()].clone();
+                        self.future.push(coord);
+                        self.on_position_change();
+                    }
+                    None => self.message = "This is the first move".to_string(),
+                }
+            }
+            Key::Char(']') => match self.future.last().cloned() {
+                Some(coord) => {
+                    let side = self.game.get_current_state().expect("Moves to step through are legal");
+                    self.play(side, coord);
+                }
+                None => self.message = "This is the last move".to_string(),
+            },
+            Key::Char('q') => return false,
+            Key::Char(_) => {}
+        }
+        true
+    }
+
+    /// Requests the evaluation of the new position and restarts the clock of the side to move.
+    fn on_position_change(&mut self) {
+        self.move_start = Instant::now();
+        self.eval = None;
+        // The evaluation thread only stops with the interface.
+        let _ = self.eval_requests.send(*self.game.get_current_turn());
+    }
+
+    /// Shows the evaluation of a position, if it is still the current one.
+    fn on_eval(&mut self, turn: Turn, eval: Option<f32>) {
+        if turn == *self.game.get_current_turn() {
+            self.eval = eval;
+        }
+    }
+
+    /// Returns the disks flipped by the last move.
+    fn get_last_flips(&self) -> Vec<Coord> {
+        let ply = self.game.get_moves().len();
+        if ply == 0 {
+            return Vec::new();
+        }
+        let before = *self.history[ply - 1].get_current_board();
+        let after = self.game.get_current_board();
+        (0..NUM_CELLS)
+            .map(|index| Coord::new(index / BOARD_SIZE, index % BOARD_SIZE))
+            .filter(|&coord| match (before.get_cell(coord), after.get_cell(coord)) {
+                (Ok(&Some(old)), Ok(&Some(new))) => old.get_side() != new.get_side(),
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Returns the move list, one line per move number with Dark's move and Light's, and `--` for passes.
+    fn get_move_list(&self) -> Vec<String> {
+        // The moves and passes, in the order of the sides to move, from Dark.
+        let mut plies: Vec<Option<Coord>> = Vec::new();
+        let mut turn = Turn::first_turn();
+        for coord in self.game.get_moves() {
+            let side = turn.get_state().expect("Played moves are legal");
+            let expected = match plies.len() % 2 {
+                0 => reversi::Side::Dark,
+                _ => reversi::Side::Light,
+            };
+            if side != expected {
+                plies.push(None);
+            }
+            plies.push(Some(coord));
+            turn.make_move(coord).expect("Played moves are legal");
+        }
+        plies.chunks(2)
+            .enumerate()
+            .map(|(index, pair)| {
+                let pair: Vec<String> = pair.iter().map(|ply| ply.map_or("--".to_string(), |coord| coord.to_string())).collect();
+                format!("{:2}. {}", index + 1, pair.join(" "))
+            })
+            .collect()
+    }
+
+    /// Draws the whole screen: the board on the left and the side panel on the right.
+    fn draw<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let turn = self.game.get_current_turn();
+        let human_to_move = self.get_running_side().is_some_and(|side| matches!(*self.get_player(side), Player::Human));
+        let legal_moves = if human_to_move { turn.get_legal_moves() } else { Vec::new() };
+        let last_move = self.game.get_moves().last().cloned();
+        let last_flips = self.get_last_flips();
+
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        screen.push_str(&goto(1, 1));
+        screen.push_str("   ");
+        for col in 0..BOARD_SIZE {
+            screen.push_str(&format!(" {} ", (b'a' + col as u8) as char));
+        }
+        for row in 0..BOARD_SIZE {
+            screen.push_str(&goto(row + 2, 1));
+            screen.push_str(&format!("{:2} ", row + 1));
+            for col in 0..BOARD_SIZE {
+                let coord = Coord::new(row, col);
+                // Background: cursor in reverse video, then last move, last flips and legal moves.
+                let background = if coord == self.cursor {
+                    "7;42"
+                } else if last_move == Some(coord) {
+                    "45"
+                } else if last_flips.contains(&coord) {
+                    "46"
+                } else if legal_moves.contains(&coord) {
+                    "43"
+                } else {
+                    "42"
+                };
+                let symbol = match *turn.get_board().get_cell(coord).expect("The coordinate is on the board") {
+                    Some(disk) if disk.get_side() == reversi::Side::Dark => "\x1b[30m\u{25cf}",
+                    Some(_) => "\x1b[97m\u{25cf}",
+                    None if legal_moves.contains(&coord) => "\x1b[30m\u{b7}",
+                    None => " ",
+                };
+                screen.push_str(&format!("\x1b[{}m {} \x1b[0m", background, symbol));
+            }
+        }
+
+        let mut panel = Vec::new();
+        let (dark, light) = turn.get_score();
+        for &(side, name, symbol, disks) in &[(reversi::Side::Dark, "Dark", 'X', dark), (reversi::Side::Light, "Light", 'O', light)] {
+            let clock = self.get_time_left(side).map_or(String::new(), |time| {
+                format!("{:02}:{:02}", time.as_secs() / 60, time.as_secs() % 60)
+            });
+            panel.push(format!("{:5} ({}) {:12} {:2}  {}", name, symbol, self.get_player(side).get_name(), disks, clock));
+        }
+        panel.push(String::new());
+        panel.push(match (self.get_running_side(), self.game.get_result()) {
+            (Some(side), _) => format!("{} to move", side),
+            (None, Some(GameResult::WinByTimeout(winner))) => format!("{} lost on time", winner.opposite()),
+            (None, Some(result)) => match result.get_winner() {
+                Some(winner) => format!("Game over: {} wins {}-{}", winner, dark, light),
+                None => format!("Game over: draw {}-{}", dark, light),
+            },
+            (None, None) => String::new(),
+        });
+        panel.push(match self.eval {
+            Some(eval) if eval > 0.0 => format!("Eval: {:+.2} (Light ahead)", eval),
+            Some(eval) if eval < 0.0 => format!("Eval: {:+.2} (Dark ahead)", eval),
+            Some(eval) => format!("Eval: {:+.2}", eval),
+            None => String::new(),
+        });
+        panel.push(String::new());
+        panel.push("Moves:".to_string());
+        let moves = self.get_move_list();
+        panel.extend(moves.iter().skip(moves.len().saturating_sub(MOVE_LIST_ROWS)).cloned());
+        for (index, line) in panel.iter().enumerate() {
+            screen.push_str(&goto(index + 1, PANEL_COLUMN));
+            screen.push_str(line);
+        }
+
+        let bottom = BOARD_SIZE.max(panel.len()) + 3;
+        screen.push_str(&goto(bottom, 1));
+        screen.push_str("arrows: move  enter: play  u: undo  [ ]: step  q: quit");
+        screen.push_str(&goto(bottom + 1, 1));
+        screen.push_str(&self.message);
+        output.write_all(screen.as_bytes())?;
+        output.flush()
+    }
+}
+
+/// Returns the escape sequence moving the terminal's cursor to the given row and column (starting from 1).
+fn goto(row: usize, col: usize) -> String {
+    format!("\x1b[{};{}H", row, col)
+}
+
+/// Spawns a thread evaluating the positions it is sent, skipping to the latest one when several are waiting,
+/// and sending back their evaluations as events.
+fn spawn_evaluator(events: Sender<Event>) -> Sender<Turn> {
+    let (request_sender, requests) = mpsc::channel::<Turn>();
+    thread::spawn(move || {
+        let mut evaluator = Engine::from_spec(EVAL_ENGINE).expect("The evaluation engine is valid");
+        while let Ok(mut turn) = requests.recv() {
+            if let Some(latest) = requests.try_iter().last() {
+                turn = latest;
+            }
+            let eval = evaluator.choose_move(&turn, 0.0, &mut rand::thread_rng()).map(|(_, score)| score);
+            if events.send(Event::Eval(turn, eval)).is_err() {
+                break;
+            }
+        }
+    });
+    request_sender
+}
+
+/// Plays the given moves from the first turn.
+fn replay(moves: &[Coord]) -> reversi::Result<TuiGame> {
+    let mut game = Game::new((), ());
+    for &coord in moves {
+        game.make_move(coord)?;
+    }
+    Ok(game)
+}
+
+/// Parses a time control given as minutes, optionally followed by `+` and an increment in seconds.
+/// Both have to be finite, and at most a day; the minutes have to be positive.
+fn parse_time_control(text: &str) -> Option<TimeControl> {
+    let seconds = |text: &str, unit: f64| {
+        text.parse::<f64>().ok().map(|value| value * unit).filter(|seconds| (0.0..=MAX_TIME).contains(seconds))
+    };
+    let mut parts = text.splitn(2, '+');
+    let base = seconds(parts.next()?, 60.0).filter(|&base| base > 0.0)?;
+    let base = Duration::from_secs_f64(base);
+    match parts.next() {
+        Some(increment) => Some(TimeControl::Fischer { base, increment: Duration::from_secs_f64(seconds(increment, 1.0)?) }),
+        None => Some(TimeControl::SuddenDeath(base)),
+    }
+}
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut dark = Player::Human;
+    let mut light = Player::from_spec("medium").expect("The default engine is valid");
+    let mut time_control = None;
+    let mut future = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--dark" => dark = Player::from_spec(&value("--dark")?).map_err(|err| err.to_string())?,
+            "--light" => light = Player::from_spec(&value("--light")?).map_err(|err| err.to_string())?,
+            "--time" => time_control = Some(parse_time_control(&value("--time")?).ok_or("Invalid time control")?),
+            "--load" => {
+                let path = value("--load")?;
+                let transcript = fs::read_to_string(&path).map_err(|err| format!("Cannot load {}: {}", path, err))?;
+                let moves = parse_coords(&transcript).map_err(|_| format!("Invalid transcript in {}", path))?;
+                replay(&moves).map_err(|_| format!("Illegal moves in {}", path))?;
+                future = moves.into_iter().rev().collect();
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let mut game = Game::new((), ());
+    if let Some(time_control) = time_control {
+        game.set_time_controls(time_control, time_control);
+    }
+    let (events, receiver) = mpsc::channel();
+    let mut tui = Tui {
+        history: vec![game.clone()],
+        game,
+        dark,
+        light,
+        move_start: Instant::now(),
+        future,
+        cursor: Coord::new(2, 3),
+        thinking: None,
+        events: events.clone(),
+        eval_requests: spawn_evaluator(events.clone()),
+        eval: None,
+        message: String::new(),
+    };
+    tui.on_position_change();
+    spawn_reader(events);
+    let raw_mode = RawMode::enable();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let io_error = |err: io::Error| err.to_string();
+    // The alternate screen keeps the terminal's contents, and the cursor is hidden.
+    write!(output, "\x1b[?1049h\x1b[?25l").map_err(io_error)?;
+    loop {
+        tui.check_time();
+        tui.draw(&mut output).map_err(io_error)?;
+        tui.start_engine();
+        match receiver.recv_timeout(REFRESH) {
+            Ok(Event::Key(key)) => {
+                if !tui.handle(key) {
+                    break;
+                }
+            }
+            Ok(Event::EngineMove(engine, coord)) => tui.on_engine_move(engine, coord),
+            Ok(Event::Eval(turn, eval)) => tui.on_eval(turn, eval),
+            Ok(Event::Closed) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+    write!(output, "\x1b[?25h\x1b[?1049l").and_then(|_| output.flush()).map_err(io_error)?;
+    drop(raw_mode);
+    Ok(())
+}
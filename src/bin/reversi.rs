@@ -0,0 +1,261 @@
+//! Plays Reversi in the terminal, between humans and built-in engines.
+//!
+//! Usage: `reversi [--dark PLAYER] [--light PLAYER] [--load FILE]`
+//!
+//! Players are `human`, an engine strength (`easy`, `medium` or `hard`) or an engine given as `random`,
+//! `search:DEPTH[:WEIGHTS]` or `mcts:ITERATIONS`. By default, a human plays Dark against a `medium` engine.
+//! Moves are entered in algebraic notation (e.g. `f5`); type `help` for the other commands.
+
+extern crate rand;
+extern crate reversi;
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+use reversi::board::*;
+use reversi::game::*;
+use reversi::engine::{Engine, Player};
+
+const USAGE: &str = "Usage: reversi [--dark PLAYER] [--light PLAYER] [--load FILE]";
+
+const HELP: &str = "\
+Commands:
+  f5                    play a move (legal moves are marked with *)
+  hint                  suggest a move
+  undo                  take back moves until the side to move can play again
+  redo                  replay the moves taken back by the last undo
+  save FILE             save the game's transcript (e.g. f5d6c3) to a file
+  load FILE             load a game from a transcript file
+  player SIDE PLAYER    set the player of a side: human, easy, medium, hard or an engine
+  help                  show this help
+  quit                  quit the game";
+
+/// The engine suggesting moves to humans.
+const HINT_ENGINE: &str = "search:4";
+
+/// The game being played, without players: moves come from the prompt and the engines.
+type CliGame = Game<(), (), ()>;
+
+/// The session: the game, its players and the moves which can be redone, last undo's first.
+struct Cli {
+    game: CliGame,
+    dark: Player,
+    light: Player,
+    redo: Vec<Vec<Coord>>,
+}
+
+impl Cli {
+    /// Executes a line typed at the prompt, writing its effects to the output.
+    /// It returns `false` when the session has to end.
+    fn execute<W: Write>(&mut self, line: &str, output: &mut W) -> io::Result<bool> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match *tokens.as_slice() {
+            [] => {}
+            ["quit"] | ["exit"] => return Ok(false),
+            ["help"] => writeln!(output, "{}", HELP)?,
+            ["hint"] => {
+                let mut engine = Engine::from_spec(HINT_ENGINE).expect("The hint engine is valid");
+                match engine.choose_move(self.game.get_current_turn(), 0.0, &mut rand::thread_rng()) {
+                    Some((coord, _)) => writeln!(output, "Hint: {}", coord)?,
+                    None => writeln!(output, "The game is over")?,
+                }
+            }
+            ["undo"] => {
+                let moves = self.game.get_moves();
+                match self.game.undo() {
+                    Ok(()) => {
+                        self.redo.push(moves[self.game.get_moves().len()..].to_vec());
+                        self.show(output)?;
+                    }
+                    Err(_) => writeln!(output, "Nothing to undo")?,
+                }
+            }
+            ["redo"] => match self.redo.pop() {
+                Some(moves) => {
+                    for coord in moves {
+                        self.game.make_move(coord).expect("Undone moves are legal");
+                    }
+                    self.show(output)?;
+                }
+                None => writeln!(output, "Nothing to redo")?,
+            },
+            ["save", path] => {
+                let transcript: String = self.game.get_moves().iter().map(|coord| coord.to_string()).collect();
+                match fs::write(path, transcript + "\n") {
+                    Ok(()) => writeln!(output, "Saved to {}", path)?,
+                    Err(err) => writeln!(output, "Cannot save to {}: {}", path, err)?,
+                }
+            }
+            ["load", path] => match load(path) {
+                Ok(game) => {
+                    self.game = game;
+                    self.redo.clear();
+                    self.show(output)?;
+                }
+                Err(message) => writeln!(output, "{}", message)?,
+            },
+            ["player", side, spec] => {
+                let player = match Player::from_spec(spec) {
+                    Ok(player) => player,
+                    Err(message) => return writeln!(output, "{}", message).map(|_| true),
+                };
+                match side {
+                    "dark" => self.dark = player,
+                    "light" => self.light = player,
+                    _ => return writeln!(output, "Unknown side {}: use dark or light", side).map(|_| true),
+                }
+                self.play_engines(output)?;
+            }
+            [text] => match text.parse::<Coord>() {
+                Ok(coord) => self.play_human(coord, output)?,
+                Err(_) => writeln!(output, "Unknown command {}: type help for the list of commands", text)?,
+            },
+            _ => writeln!(output, "Unknown command: type help for the list of commands")?,
+        }
+        Ok(true)
+    }
+
+    /// Plays a move typed by a human, then the engines' replies.
+    fn play_human<W: Write>(&mut self, coord: Coord, output: &mut W) -> io::Result<()> {
+        match self.game.get_current_state() {
+            Some(side) if matches!(*self.get_player(side), Player::Human) => match self.game.make_move(coord) {
+                Ok(()) => {
+                    self.redo.clear();
+                    self.show(output)?;
+                    self.play_engines(output)
+                }
+                Err(_) => writeln!(output, "Illegal move {}", coord),
+            },
+            Some(_) => writeln!(output, "It is not your turn"),
+            None => writeln!(output, "The game is over"),
+        }
+    }
+
+    /// Has the engines play as long as it is their turn.
+    fn play_engines<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+        while let Some(side) = self.game.get_current_state() {
+            let turn = *self.game.get_current_turn();
+            let (name, coord) = match *self.get_player_mut(side) {
+                Player::Human => break,
+                Player::Engine(ref name, ref mut engine) => {
+                    let (coord, _) = engine.choose_move(&turn, 0.0, &mut rand::thread_rng()).expect("The game is running");
+                    (name.clone(), coord)
+                }
+            };
+            self.game.make_move(coord).expect("Engines play legal moves");
+            self.redo.clear();
+            writeln!(output, "{} ({}) plays {}", side, name, coord)?;
+            self.show(output)?;
+        }
+        Ok(())
+    }
+
+    /// Draws the board, the score and the side to move (or the result).
+    fn show<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let turn = self.game.get_current_turn();
+        let legal_moves = turn.get_legal_moves();
+        let last_move = self.game.get_moves().last().cloned();
+        write!(output, "\n   ")?;
+        for col in 0..BOARD_SIZE {
+            write!(output, " {}", (b'a' + col as u8) as char)?;
+        }
+        writeln!(output)?;
+        for row in 0..BOARD_SIZE {
+            write!(output, "{:2} ", row + 1)?;
+            for col in 0..BOARD_SIZE {
+                let coord = Coord::new(row, col);
+                let symbol = match *turn.get_board().get_cell(coord).expect("The coordinate is on the board") {
+                    Some(disk) if disk.get_side() == reversi::Side::Dark => 'X',
+                    Some(_) => 'O',
+                    None if legal_moves.contains(&coord) => '*',
+                    None => '.',
+                };
+                let separator = if last_move == Some(coord) { '>' } else { ' ' };
+                write!(output, "{}{}", separator, symbol)?;
+            }
+            writeln!(output)?;
+        }
+        let (dark, light) = turn.get_score();
+        writeln!(output, "Dark (X) {} - {} Light (O)", dark, light)?;
+        match (self.game.get_current_state(), self.game.get_result()) {
+            (Some(side), _) => writeln!(output, "{} to move", side),
+            (None, Some(result)) => match result.get_winner() {
+                Some(winner) => writeln!(output, "Game over: {} wins", winner),
+                None => writeln!(output, "Game over: draw"),
+            },
+            (None, None) => Ok(()),
+        }
+    }
+
+    /// Returns the player of the given side.
+    fn get_player(&self, side: reversi::Side) -> &Player {
+        match side {
+            reversi::Side::Dark => &self.dark,
+            reversi::Side::Light => &self.light,
+        }
+    }
+
+    /// Returns the player of the given side, mutably.
+    fn get_player_mut(&mut self, side: reversi::Side) -> &mut Player {
+        match side {
+            reversi::Side::Dark => &mut self.dark,
+            reversi::Side::Light => &mut self.light,
+        }
+    }
+}
+
+/// Loads a game from a transcript file.
+fn load(path: &str) -> Result<CliGame, String> {
+    let transcript = fs::read_to_string(path).map_err(|err| format!("Cannot load {}: {}", path, err))?;
+    let moves = parse_coords(&transcript).map_err(|_| format!("Invalid transcript in {}", path))?;
+    let mut game = Game::new((), ());
+    for coord in moves {
+        game.make_move(coord).map_err(|_| format!("Illegal move {} in {}", coord, path))?;
+    }
+    Ok(game)
+}
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut dark = Player::Human;
+    let mut light = Player::from_spec("medium").expect("The default engine is valid");
+    let mut game = Game::new((), ());
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}\n{}", name, USAGE));
+        match arg.as_str() {
+            "--dark" => dark = Player::from_spec(&value("--dark")?).map_err(|err| err.to_string())?,
+            "--light" => light = Player::from_spec(&value("--light")?).map_err(|err| err.to_string())?,
+            "--load" => game = load(&value("--load")?)?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let mut cli = Cli { game, dark, light, redo: Vec::new() };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let io_error = |err: io::Error| err.to_string();
+    writeln!(output, "Type help for the list of commands.").map_err(io_error)?;
+    cli.show(&mut output).map_err(io_error)?;
+    cli.play_engines(&mut output).map_err(io_error)?;
+    let mut lines = stdin.lock().lines();
+    loop {
+        write!(output, "> ").and_then(|_| output.flush()).map_err(io_error)?;
+        let line = match lines.next() {
+            Some(line) => line.map_err(io_error)?,
+            None => break,
+        };
+        if !cli.execute(line.trim(), &mut output).map_err(io_error)? {
+            break;
+        }
+    }
+    writeln!(output).map_err(io_error)
+}
@@ -1,6 +1,7 @@
 //! Implementation of a 2D board (and of its constituing elements) with coordinates and iterators.
 
 use std::fmt;
+use std::str::FromStr;
 use ::Result;
 
 /// The number of cells per side of the board.
@@ -38,6 +39,23 @@ impl Direction {
             Direction::NW => Direction::SE,
         }
     }
+
+    /// Produces the image of the direction through a symmetry of the board.
+    #[inline(always)]
+    pub fn transform(&self, symmetry: Symmetry) -> Direction {
+        // Directions are listed clockwise, so symmetries act on their indexes modulo 8.
+        let index = DIRECTIONS.iter().position(|dir| dir == self).expect("Every direction is listed");
+        DIRECTIONS[match symmetry {
+            Symmetry::Identity => index,
+            Symmetry::Rotate90 => index + 2,
+            Symmetry::Rotate180 => index + 4,
+            Symmetry::Rotate270 => index + 6,
+            Symmetry::FlipHorizontal => 8 - index,
+            Symmetry::FlipVertical => 12 - index,
+            Symmetry::FlipDiagonal => 14 - index,
+            Symmetry::FlipAntiDiagonal => 10 - index,
+        } % 8]
+    }
 }
 
 /// Lists all cardinal directions from `Direction`.
@@ -52,9 +70,48 @@ pub const DIRECTIONS: [Direction; 8] = [
     Direction::NW
 ];
 
+/// Enums the eight symmetries of the board: rotations are clockwise,
+/// horizontal and vertical flips mirror columns and rows respectively,
+/// diagonal flips mirror the board along its main (`a1`-`h8`) or anti (`h1`-`a8`) diagonal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    FlipDiagonal,
+    FlipAntiDiagonal,
+}
+
+impl Symmetry {
+    /// Returns the symmetry undoing this one.
+    #[inline(always)]
+    pub fn inverse(&self) -> Symmetry {
+        match *self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+}
+
+/// Lists all symmetries from `Symmetry`.
+pub const SYMMETRIES: [Symmetry; 8] = [
+    Symmetry::Identity,
+    Symmetry::Rotate90,
+    Symmetry::Rotate180,
+    Symmetry::Rotate270,
+    Symmetry::FlipHorizontal,
+    Symmetry::FlipVertical,
+    Symmetry::FlipDiagonal,
+    Symmetry::FlipAntiDiagonal,
+];
+
 /// Coordinates of a cell, given by a row and a column.
 /// Follows matrices conventions (see <https://en.wikipedia.org/wiki/Matrix_(mathematics)>) but for starting indexes at 0.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Coord(usize, usize);
 
 impl Coord {
@@ -94,10 +151,72 @@ impl Coord {
             Direction::NW       => Coord::new(self.0.wrapping_sub(1), self.1.wrapping_sub(1)),
         }
     }
+
+    /// Produces the image of the coordinates through a symmetry of the board.
+    #[inline(always)]
+    pub fn transform(&self, symmetry: Symmetry) -> Coord {
+        let last = BOARD_SIZE - 1;
+        let (row, col) = self.get_row_col();
+        match symmetry {
+            Symmetry::Identity => Coord::new(row, col),
+            Symmetry::Rotate90 => Coord::new(col, last - row),
+            Symmetry::Rotate180 => Coord::new(last - row, last - col),
+            Symmetry::Rotate270 => Coord::new(last - col, row),
+            Symmetry::FlipHorizontal => Coord::new(row, last - col),
+            Symmetry::FlipVertical => Coord::new(last - row, col),
+            Symmetry::FlipDiagonal => Coord::new(col, row),
+            Symmetry::FlipAntiDiagonal => Coord::new(last - col, last - row),
+        }
+    }
+}
+
+/// Coordinates are written in algebraic notation: a letter for the column (starting from `a`)
+/// followed by the row's number (starting from 1). For example, `Coord::new(4, 5)` is written `f5`.
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.1 as u8) as char, self.0 + 1)
+    }
+}
+
+/// Parses coordinates in algebraic notation, either lowercase or uppercase.
+impl FromStr for Coord {
+    type Err = ::ReversiError;
+
+    fn from_str(s: &str) -> Result<Coord> {
+        let mut chars = s.chars();
+        let col = match chars.next() {
+            Some(letter) if letter.is_ascii_alphabetic() => (letter.to_ascii_lowercase() as u8 - b'a') as usize,
+            _ => return Err(::ReversiError::InvalidNotation),
+        };
+        // The row is a single digit, without sign or leading zero.
+        let row = match (chars.next().and_then(|digit| digit.to_digit(10)), chars.next()) {
+            (Some(row), None) => row as usize,
+            _ => return Err(::ReversiError::InvalidNotation),
+        };
+        if row == 0 || row > BOARD_SIZE || col >= BOARD_SIZE {
+            return Err(::ReversiError::InvalidNotation);
+        }
+        Ok(Coord::new(row - 1, col))
+    }
+}
+
+/// Parses a sequence of coordinates in algebraic notation, such as a game's transcript (e.g. `f5d6c3`).
+/// Coordinates can be separated by whitespace or not.
+pub fn parse_coords(s: &str) -> Result<Vec<Coord>> {
+    let mut coords = Vec::new();
+    let mut rest = s.trim_start();
+    while !rest.is_empty() {
+        let end = rest.char_indices().skip(1)
+            .find(|&(_, c)| !c.is_ascii_digit())
+            .map_or(rest.len(), |(index, _)| index);
+        coords.push(rest[..end].parse()?);
+        rest = rest[end..].trim_start();
+    }
+    Ok(coords)
 }
 
 /// A disk is characterized by its two sides, one Dark and one Light.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Disk(::Side);
 
 impl Disk {
@@ -123,7 +242,8 @@ impl Disk {
 /// Each cell in the board can either be empty or taken by one of the players.
 pub type Cell = Option<Disk>;
 
-#[derive(Clone, Copy)]
+/// Boards are ordered cell by cell in row-major order, with empty cells first, then Dark and Light disks.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Board([[Cell; BOARD_SIZE]; BOARD_SIZE]);
 
 impl fmt::Debug for Board {
@@ -185,4 +305,25 @@ impl Board {
         &self.0
     }
 
+    /// Produces the image of the board through a symmetry.
+    pub fn transform(&self, symmetry: Symmetry) -> Board {
+        let mut board = [[None; BOARD_SIZE]; BOARD_SIZE];
+        for (row, cells) in self.0.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                let image = Coord::new(row, col).transform(symmetry);
+                board[image.get_row()][image.get_col()] = cell;
+            }
+        }
+        Board(board)
+    }
+
+    /// Returns the canonical representative of the board's symmetry class, that is, its smallest image,
+    /// together with the symmetry mapping the board to it.
+    pub fn canonical(&self) -> (Board, Symmetry) {
+        SYMMETRIES.iter()
+            .map(|&symmetry| (self.transform(symmetry), symmetry))
+            .min_by(|a, b| a.0.cmp(&b.0))
+            .expect("There are eight symmetries")
+    }
+
 }
@@ -0,0 +1,340 @@
+//! Implementation of an opening book, mapping positions to the moves played from them.
+//! Positions are normalized by the board's symmetries, so that equivalent openings share their entries.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use rand::Rng;
+use board::*;
+use turn::*;
+use game::*;
+use ::Result;
+
+/// The magic bytes opening a book file.
+const BOOK_MAGIC: &[u8; 4] = b"RVBK";
+
+/// The version of the book file format.
+const BOOK_VERSION: u32 = 1;
+
+/// A position's key: the state of each cell (0 for empty, 1 for Dark, 2 for Light) in row-major order,
+/// followed by the side to move.
+type Key = [u8; NUM_CELLS + 1];
+
+/// A move stored in the book, together with its statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookMove {
+    coord: Coord,
+    score: f32,
+    count: u32,
+}
+
+impl BookMove {
+    /// Returns the move's coordinates.
+    #[inline(always)]
+    pub fn get_coord(&self) -> Coord {
+        self.coord
+    }
+
+    /// Returns the average score of the games in which the move was played
+    /// (positive when Light is winning, as in `Turn::get_score_diff`).
+    #[inline(always)]
+    pub fn get_score(&self) -> f32 {
+        self.score
+    }
+
+    /// Returns how many times the move was played.
+    #[inline(always)]
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// An opening book. Moves are stored relative to the canonical form of their position,
+/// and are mapped back to the queried position when looked up.
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    positions: HashMap<Key, Vec<BookMove>>,
+}
+
+impl Book {
+    /// Creates a new empty book.
+    pub fn new() -> Book {
+        Book::default()
+    }
+
+    /// Returns the number of positions in the book.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns whether the book has no positions.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Records that the given move was played from the given turn, in a game with the given score.
+    /// The move's count is increased and its score updated to the average of all recorded scores.
+    pub fn add_move(&mut self, turn: &Turn, coord: Coord, score: f32) -> Result<()> {
+        turn.check_move(coord)?;
+        let (key, symmetry) = canonical_key(turn);
+        let coord = coord.transform(symmetry);
+        let moves = self.positions.entry(key).or_default();
+        match moves.iter_mut().find(|book_move| book_move.coord == coord) {
+            Some(book_move) => {
+                book_move.count += 1;
+                book_move.score += (score - book_move.score) / book_move.count as f32;
+            }
+            None => moves.push(BookMove { coord, score, count: 1 }),
+        }
+        Ok(())
+    }
+
+    /// Adds the first `max_plies` moves of a game, given as the sequence of its moves from the first turn.
+    /// Moves are scored with the score difference of the last turn reached by the sequence.
+    /// The book is left untouched if the sequence contains an illegal move.
+    pub fn add_game(&mut self, moves: &[Coord], max_plies: usize) -> Result<()> {
+        let mut turns = Vec::with_capacity(moves.len());
+        let mut turn = Turn::first_turn();
+        for &coord in moves {
+            turns.push(turn);
+            turn.make_move(coord)?;
+        }
+        let score = turn.get_score_diff() as f32;
+        for (turn, &coord) in turns.iter().zip(moves).take(max_plies) {
+            self.add_move(turn, coord, score)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a collection of games, one per line in algebraic notation (e.g. `f5d6c3d3c4`), up to `max_plies` moves each.
+    /// Empty lines are skipped. It returns the number of games added.
+    pub fn add_games_from<R: BufRead>(&mut self, reader: R, max_plies: usize) -> io::Result<usize> {
+        let mut games = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let moves = parse_coords(&line).map_err(|_| invalid("Invalid game notation"))?;
+            self.add_game(&moves, max_plies).map_err(|_| invalid("Illegal move in game"))?;
+            games += 1;
+        }
+        Ok(games)
+    }
+
+    /// Returns the book moves for the given turn, relative to it. The list is empty if the turn is out of book.
+    pub fn get_moves(&self, turn: &Turn) -> Vec<BookMove> {
+        if turn.is_end_state() {
+            return Vec::new();
+        }
+        let (key, symmetry) = canonical_key(turn);
+        let inverse = symmetry.inverse();
+        self.positions.get(&key)
+            .map(|moves| moves.iter()
+                .map(|book_move| BookMove { coord: book_move.coord.transform(inverse), ..*book_move })
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Chooses one of the book moves for the given turn at random, with probability proportional to its count.
+    /// It returns `None` if the turn is out of book.
+    pub fn choose_move<R: Rng>(&self, turn: &Turn, rng: &mut R) -> Option<Coord> {
+        let moves = self.get_moves(turn);
+        let total: u64 = moves.iter().map(|book_move| book_move.count as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = rng.gen_range(0, total);
+        for book_move in moves {
+            if pick < book_move.count as u64 {
+                return Some(book_move.coord);
+            }
+            pick -= book_move.count as u64;
+        }
+        None
+    }
+
+    /// Loads a book from a book file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Book> {
+        Book::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Saves the book to a book file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a book in the binary book format: magic bytes, then version, board size and number of positions
+    /// as little-endian `u32`s. Each position is given by its key (one byte per cell, then one for the side to move)
+    /// and its number of moves as a little-endian `u16`, followed by the moves: row and column bytes,
+    /// score as a little-endian `f32` and count as a little-endian `u32`.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Book> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != BOOK_MAGIC {
+            return Err(invalid("Not a book file"));
+        }
+        if read_u32(reader)? != BOOK_VERSION {
+            return Err(invalid("Unsupported book file version"));
+        }
+        if read_u32(reader)? as usize != BOARD_SIZE {
+            return Err(invalid("Book is for a different board size"));
+        }
+        let mut book = Book::new();
+        for _ in 0..read_u32(reader)? {
+            let mut key = [0u8; NUM_CELLS + 1];
+            reader.read_exact(&mut key)?;
+            if key[..NUM_CELLS].iter().any(|&cell| cell > 2) || key[NUM_CELLS] == 0 || key[NUM_CELLS] > 2 {
+                return Err(invalid("Invalid position"));
+            }
+            let mut length = [0u8; 2];
+            reader.read_exact(&mut length)?;
+            let mut moves = Vec::with_capacity(u16::from_le_bytes(length) as usize);
+            for _ in 0..u16::from_le_bytes(length) {
+                let mut coord = [0u8; 2];
+                reader.read_exact(&mut coord)?;
+                if coord[0] as usize >= BOARD_SIZE || coord[1] as usize >= BOARD_SIZE {
+                    return Err(invalid("Move out of the board"));
+                }
+                let mut score = [0u8; 4];
+                reader.read_exact(&mut score)?;
+                moves.push(BookMove {
+                    coord: Coord::new(coord[0] as usize, coord[1] as usize),
+                    score: f32::from_le_bytes(score),
+                    count: read_u32(reader)?,
+                });
+            }
+            book.positions.insert(key, moves);
+        }
+        Ok(book)
+    }
+
+    /// Writes the book in the binary book format (see `read_from`).
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(BOOK_MAGIC)?;
+        for &value in &[BOOK_VERSION, BOARD_SIZE as u32, self.positions.len() as u32] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        // Positions are sorted to make files reproducible.
+        let mut positions: Vec<(&Key, &Vec<BookMove>)> = self.positions.iter().collect();
+        positions.sort_by_key(|&(key, _)| key);
+        for (key, moves) in positions {
+            writer.write_all(key)?;
+            writer.write_all(&(moves.len() as u16).to_le_bytes())?;
+            for book_move in moves {
+                let (row, col) = book_move.coord.get_row_col();
+                writer.write_all(&[row as u8, col as u8])?;
+                writer.write_all(&book_move.score.to_le_bytes())?;
+                writer.write_all(&book_move.count.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A player which plays from an opening book as long as the game is in book,
+/// and asks another player (typically a search engine) otherwise.
+pub struct BookPlayer<P, R> {
+    book: Book,
+    player: P,
+    rng: RefCell<R>,
+}
+
+impl<P, R: Rng> BookPlayer<P, R> {
+    /// Creates a new player choosing book moves with the given random number generator,
+    /// and falling back to the given player.
+    pub fn new(book: Book, player: P, rng: R) -> BookPlayer<P, R> {
+        BookPlayer {
+            book,
+            player,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Returns the player's book.
+    #[inline(always)]
+    pub fn get_book(&self) -> &Book {
+        &self.book
+    }
+
+    /// Returns the player used out of book.
+    #[inline(always)]
+    pub fn get_player(&self) -> &P {
+        &self.player
+    }
+}
+
+impl<A, P: IsPlayer<A>, R: Rng> IsPlayer<A> for BookPlayer<P, R> {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<A>> {
+        match self.book.choose_move(turn, &mut *self.rng.borrow_mut()) {
+            Some(coord) => Ok(PlayerAction::Move(coord)),
+            None => self.player.make_move(turn),
+        }
+    }
+}
+
+impl<A, P: IsStatefulPlayer<A>, R: Rng> IsStatefulPlayer<A> for BookPlayer<P, R> {
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>> {
+        match self.book.choose_move(turn, self.rng.get_mut()) {
+            Some(coord) => Ok(PlayerAction::Move(coord)),
+            None => self.player.make_move(turn),
+        }
+    }
+
+    fn on_game_start(&mut self, side: ::Side, turn: &Turn) {
+        self.player.on_game_start(side, turn)
+    }
+
+    fn on_opponent_move(&mut self, coord: Coord, turn: &Turn) {
+        self.player.on_opponent_move(coord, turn)
+    }
+
+    fn on_undo(&mut self, turn: &Turn) {
+        self.player.on_undo(turn)
+    }
+
+    fn on_game_end(&mut self, result: &GameResult) {
+        self.player.on_game_end(result)
+    }
+
+    fn start_pondering(&mut self, turn: &Turn) {
+        self.player.start_pondering(turn)
+    }
+}
+
+/// Returns the key of the canonical form of the turn, together with the symmetry mapping the turn to it.
+fn canonical_key(turn: &Turn) -> (Key, Symmetry) {
+    let (canonical, symmetry) = turn.canonical();
+    let mut key = [0u8; NUM_CELLS + 1];
+    for (index, cell) in canonical.get_board().get_board().iter().flat_map(|row| row.iter()).enumerate() {
+        key[index] = match *cell {
+            None => 0,
+            Some(disk) if disk.get_side() == ::Side::Dark => 1,
+            Some(_) => 2,
+        };
+    }
+    key[NUM_CELLS] = match canonical.get_state() {
+        None => 0,
+        Some(::Side::Dark) => 1,
+        Some(::Side::Light) => 2,
+    };
+    (key, symmetry)
+}
+
+/// Builds an `InvalidData` error with the given message.
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Reads a little-endian `u32`.
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
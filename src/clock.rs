@@ -0,0 +1,108 @@
+//! Implementation of time controls and of the players' clocks.
+
+use std::time::Duration;
+
+/// The time controls a game can be played with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeControl {
+    /// Each player has a fixed amount of time for the whole game.
+    SuddenDeath(Duration),
+    /// Each player has a starting amount of time, and gains `increment` after each move made in time.
+    Fischer {
+        base: Duration,
+        increment: Duration,
+    },
+    /// Once the main time is over, each move has to be made within `period`.
+    /// Every move exceeding it uses up one of the `periods`, and when none is left the player loses on time.
+    ByoYomi {
+        main: Duration,
+        period: Duration,
+        periods: u8,
+    },
+}
+
+/// A player's clock, keeping track of the time left under a given time control.
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    time_control: TimeControl,
+    main_time: Duration,
+    periods: u8,
+    flagged: bool,
+}
+
+impl Clock {
+    /// Creates a new clock, with the full starting time of the given time control.
+    pub fn new(time_control: TimeControl) -> Clock {
+        let (main_time, periods) = match time_control {
+            TimeControl::SuddenDeath(time) => (time, 0),
+            TimeControl::Fischer { base, .. } => (base, 0),
+            TimeControl::ByoYomi { main, periods, .. } => (main, periods),
+        };
+        Clock {
+            time_control,
+            main_time,
+            periods,
+            flagged: false,
+        }
+    }
+
+    /// Returns the clock's time control.
+    #[inline(always)]
+    pub fn get_time_control(&self) -> TimeControl {
+        self.time_control
+    }
+
+    /// Returns what is left of the main time.
+    #[inline(always)]
+    pub fn get_main_time(&self) -> Duration {
+        self.main_time
+    }
+
+    /// Returns how many byo-yomi periods are left.
+    #[inline(always)]
+    pub fn get_periods(&self) -> u8 {
+        self.periods
+    }
+
+    /// Returns how much time the player can spend on the next move before losing on time.
+    pub fn get_time_left(&self) -> Duration {
+        match self.time_control {
+            TimeControl::ByoYomi { period, .. } => self.main_time + period * self.periods as u32,
+            _ => self.main_time,
+        }
+    }
+
+    /// Returns whether the player has run out of time.
+    #[inline(always)]
+    pub fn is_flagged(&self) -> bool {
+        self.flagged
+    }
+
+    /// Charges the time spent on a move to the clock.
+    /// It returns `false` if the player has run out of time.
+    pub fn charge(&mut self, elapsed: Duration) -> bool {
+        if self.flagged {
+            return false;
+        }
+        if elapsed <= self.main_time {
+            self.main_time -= elapsed;
+            if let TimeControl::Fischer { increment, .. } = self.time_control {
+                self.main_time += increment;
+            }
+            return true;
+        }
+        let mut overtime = elapsed - self.main_time;
+        self.main_time = Duration::from_secs(0);
+        if let TimeControl::ByoYomi { period, .. } = self.time_control {
+            while self.periods > 0 {
+                if overtime <= period {
+                    return true;
+                }
+                overtime -= period;
+                self.periods -= 1;
+            }
+        }
+        self.flagged = true;
+        false
+    }
+}
@@ -0,0 +1,179 @@
+//! Implementation of the engines, which choose moves by themselves, and of the players of the interactive front-ends.
+
+use std::f64;
+use std::io;
+use rand::{self, Rng};
+use board::*;
+use turn::*;
+use game::*;
+use eval::*;
+use search::*;
+use mcts::*;
+use ::Result;
+
+/// What the scores of engines measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreUnit {
+    /// Nothing: random engines score every move zero.
+    None,
+    /// The estimated final score difference, positive when Light is winning.
+    Disks,
+    /// The estimated probability that Light wins.
+    Probability,
+}
+
+/// The engines which can play self-play games.
+pub enum Engine {
+    /// Plays uniformly random moves.
+    Random,
+    /// Searches with alpha-beta pruning.
+    Search(SearchPlayer<PatternEvaluator>),
+    /// Searches with Monte Carlo Tree Search.
+    Mcts(MctsPlayer),
+}
+
+impl Engine {
+    /// Creates an engine from its description: `random`, `search:DEPTH` (optionally followed by `:WEIGHTS`,
+    /// the path of a weights file for the evaluator) or `mcts:ITERATIONS`.
+    /// The strengths `easy`, `medium` and `hard` stand for searches of depth 1, 3 and 6.
+    /// Depths and iterations have to be positive.
+    pub fn from_spec(spec: &str) -> io::Result<Engine> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid engine {}", spec));
+        let spec = match spec {
+            "easy" => "search:1",
+            "medium" => "search:3",
+            "hard" => "search:6",
+            _ => spec,
+        };
+        let mut tokens = spec.splitn(3, ':');
+        match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some("random"), None, None) => Ok(Engine::Random),
+            (Some("search"), Some(depth), weights) => {
+                let depth = depth.parse().ok().filter(|&depth| depth > 0).ok_or_else(invalid)?;
+                let evaluator = match weights {
+                    Some(path) => PatternEvaluator::load(path)?,
+                    None => PatternEvaluator::new(1),
+                };
+                Ok(Engine::Search(SearchPlayer::new(evaluator, depth)))
+            }
+            (Some("mcts"), Some(iterations), None) => {
+                let iterations = iterations.parse().ok().filter(|&iterations| iterations > 0).ok_or_else(invalid)?;
+                Ok(Engine::Mcts(MctsPlayer::new(MctsLimit::Iterations(iterations))))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Seeds the engine's own random number generator, if it has one.
+    pub fn set_seed(&mut self, seed: [u32; 4]) {
+        if let Engine::Mcts(ref mut player) = *self {
+            player.set_seed(seed);
+        }
+    }
+
+    /// Returns what the engine's scores measure.
+    pub fn get_score_unit(&self) -> ScoreUnit {
+        match *self {
+            Engine::Random => ScoreUnit::None,
+            Engine::Search(_) => ScoreUnit::Disks,
+            Engine::Mcts(_) => ScoreUnit::Probability,
+        }
+    }
+
+    /// Chooses a move for the given turn, returning it together with its score, from Light's viewpoint
+    /// (see `get_score_unit`): the estimated score difference for search engines, the estimated probability
+    /// that Light wins for MCTS engines and zero for random engines.
+    ///
+    /// With zero temperature, the engine plays its best move. Otherwise, search engines choose moves with
+    /// probability proportional to `exp(score / temperature)` (scores taken from the side to move's viewpoint),
+    /// and MCTS engines with probability proportional to `visits ^ (1 / temperature)`.
+    /// It returns `None` if the turn is ended.
+    pub fn choose_move<R: Rng>(&mut self, turn: &Turn, temperature: f64, rng: &mut R) -> Option<(Coord, f32)> {
+        let side = turn.get_state()?;
+        match *self {
+            Engine::Random => rng.choose(&turn.get_legal_moves()).map(|&coord| (coord, 0.0)),
+            Engine::Search(ref player) if temperature > 0.0 => {
+                let moves = player.score_moves(turn);
+                let sign = if side == ::Side::Light { 1.0 } else { -1.0 };
+                let best = moves.iter().map(|&(_, score)| sign * score as f64).fold(f64::NEG_INFINITY, f64::max);
+                let weights: Vec<f64> = moves.iter()
+                    .map(|&(_, score)| ((sign * score as f64 - best) / temperature).exp())
+                    .collect();
+                Some(moves[sample(&weights, rng)])
+            }
+            Engine::Search(ref player) => player.search(turn),
+            Engine::Mcts(ref mut player) => {
+                let moves = player.analyse(turn);
+                let index = if temperature > 0.0 {
+                    let weights: Vec<f64> = moves.iter().map(|&(_, visits, _)| (visits as f64).powf(1.0 / temperature)).collect();
+                    sample(&weights, rng)
+                } else {
+                    (0..moves.len()).max_by_key(|&index| moves[index].1)?
+                };
+                let to_light = |win_rate: f64| if side == ::Side::Light { win_rate } else { 1.0 - win_rate };
+                moves.get(index).map(|&(coord, _, win_rate)| (coord, to_light(win_rate) as f32))
+            }
+        }
+    }
+
+    /// Prepares the engine for a new game.
+    pub fn reset(&mut self) {
+        if let Engine::Mcts(ref mut player) = *self {
+            player.reset();
+        }
+    }
+}
+
+impl<A> IsStatefulPlayer<A> for Engine {
+    /// Plays the engine's best move (or a random one, for random engines).
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>> {
+        self.choose_move(turn, 0.0, &mut rand::thread_rng())
+            .map(|(coord, _)| PlayerAction::Move(coord))
+            .ok_or(::ReversiError::EndedGame(*turn))
+    }
+
+    fn on_game_start(&mut self, _side: ::Side, _turn: &Turn) {
+        self.reset();
+    }
+
+    fn on_undo(&mut self, _turn: &Turn) {
+        self.reset();
+    }
+}
+
+/// A player of the interactive front-ends: either a human, whose moves come from the user interface,
+/// or an engine together with the description it was created from.
+pub enum Player {
+    Human,
+    Engine(String, Engine),
+}
+
+impl Player {
+    /// Creates a player from its description: `human` or an engine's (see `Engine::from_spec`).
+    pub fn from_spec(spec: &str) -> io::Result<Player> {
+        if spec == "human" {
+            return Ok(Player::Human);
+        }
+        Engine::from_spec(spec).map(|engine| Player::Engine(spec.to_string(), engine))
+    }
+
+    /// Returns the player's name: `human` or the engine's description.
+    pub fn get_name(&self) -> &str {
+        match *self {
+            Player::Human => "human",
+            Player::Engine(ref name, _) => name,
+        }
+    }
+}
+
+/// Chooses an index at random, with probability proportional to its weight.
+fn sample<R: Rng>(weights: &[f64], rng: &mut R) -> usize {
+    let mut pick = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (index, &weight) in weights.iter().enumerate() {
+        if pick < weight {
+            return index;
+        }
+        pick -= weight;
+    }
+    weights.len() - 1
+}
@@ -0,0 +1,280 @@
+//! Implementation of evaluation functions, estimating the final score difference of a turn.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use board::*;
+use turn::*;
+
+/// Being able to estimate the final score of a turn is the trait characterizing evaluators.
+/// As `Turn::get_score_diff`, evaluations are positive when Light is winning and negative when Dark is.
+pub trait IsEvaluator {
+    fn evaluate(&self, turn: &Turn) -> f32;
+}
+
+impl<'e, E: 'e + ?Sized + IsEvaluator> IsEvaluator for &'e E {
+    #[inline(always)]
+    fn evaluate(&self, turn: &Turn) -> f32 {
+        (**self).evaluate(turn)
+    }
+}
+
+/// The simplest evaluator, which takes the current score difference as estimate.
+#[derive(Debug, Clone, Copy)]
+pub struct DiskDifference;
+
+impl IsEvaluator for DiskDifference {
+    #[inline(always)]
+    fn evaluate(&self, turn: &Turn) -> f32 {
+        turn.get_score_diff() as f32
+    }
+}
+
+/// A rough evaluation of how good it is to take a square: corners are best, squares next to them are worst.
+pub fn square_value(coord: Coord) -> i8 {
+    let last = BOARD_SIZE - 1;
+    let distance = |index: usize| if index < BOARD_SIZE / 2 { index } else { last - index };
+    match (distance(coord.get_row()), distance(coord.get_col())) {
+        (0, 0) => 4,
+        (1, 1) => -2,
+        (0, 1) | (1, 0) => -1,
+        (0, _) | (_, 0) => 2,
+        (1, _) | (_, 1) => 0,
+        _ => 1,
+    }
+}
+
+/// The magic bytes opening a weights file.
+const WEIGHTS_MAGIC: &[u8; 4] = b"RVPW";
+
+/// The version of the weights file format.
+const WEIGHTS_VERSION: u32 = 1;
+
+/// The number of states a cell can be in (empty, Dark or Light).
+const CELL_STATES: usize = 3;
+
+/// A pattern is a set of cells whose configuration is given a weight.
+/// All its instances (its images through the board's symmetries) share the same weights.
+#[derive(Debug, Clone)]
+struct Pattern {
+    instances: Vec<Vec<Coord>>,
+    offset: usize,
+}
+
+impl Pattern {
+    /// Creates a pattern from the cells of one of its instances, with weights starting at the given offset.
+    fn new(cells: Vec<Coord>, offset: usize) -> Pattern {
+        let mut instances: Vec<Vec<Coord>> = Vec::new();
+        let mut cell_sets: Vec<Vec<Coord>> = Vec::new();
+        for &symmetry in SYMMETRIES.iter() {
+            let instance: Vec<Coord> = cells.iter().map(|coord| coord.transform(symmetry)).collect();
+            let mut cell_set = instance.clone();
+            cell_set.sort();
+            if !cell_sets.contains(&cell_set) {
+                cell_sets.push(cell_set);
+                instances.push(instance);
+            }
+        }
+        Pattern { instances, offset }
+    }
+
+    /// The number of configurations of the pattern.
+    #[inline(always)]
+    fn get_size(&self) -> usize {
+        CELL_STATES.pow(self.instances[0].len() as u32)
+    }
+
+    /// Returns the index of the configuration of the given instance.
+    #[inline(always)]
+    fn get_index(&self, board: &Board, instance: &[Coord]) -> usize {
+        instance.iter().fold(0, |index, &coord| {
+            index * CELL_STATES + match *board.get_cell(coord).expect("Patterns are within the board") {
+                None => 0,
+                Some(disk) if disk.get_side() == ::Side::Dark => 1,
+                Some(_) => 2,
+            }
+        })
+    }
+}
+
+/// An evaluator summing the weights of the configurations of edge, corner, diagonal and 2x5-corner patterns.
+/// Weights depend on the stage of the game, which is given by the turn's tempo.
+#[derive(Debug, Clone)]
+pub struct PatternEvaluator {
+    patterns: Vec<Pattern>,
+    stages: usize,
+    stage_size: usize,
+    weights: Vec<f32>,
+}
+
+impl PatternEvaluator {
+    /// Creates a new evaluator with the given number of game stages.
+    /// Weights are initialized to a simple positional evaluation, which is the same for every stage.
+    pub fn new(stages: usize) -> PatternEvaluator {
+        assert!(stages > 0, "There has to be at least one stage");
+        let mut base_patterns = vec![
+            // Edge
+            (0..BOARD_SIZE).map(|col| Coord::new(0, col)).collect::<Vec<Coord>>(),
+            // 3x3 corner
+            (0..9).map(|index| Coord::new(index / 3, index % 3)).collect(),
+            // 2x5 corner
+            (0..10).map(|index| Coord::new(index / 5, index % 5)).collect(),
+        ];
+        // Diagonals, from the main one down to the ones of length 4
+        for length in (4..BOARD_SIZE + 1).rev() {
+            base_patterns.push((0..length).map(|index| Coord::new(index, index + BOARD_SIZE - length)).collect());
+        }
+        let mut patterns = Vec::new();
+        let mut stage_size = 0;
+        for cells in base_patterns {
+            let pattern = Pattern::new(cells, stage_size);
+            stage_size += pattern.get_size();
+            patterns.push(pattern);
+        }
+
+        let mut evaluator = PatternEvaluator {
+            patterns,
+            stages,
+            stage_size,
+            weights: vec![0.0; stages * stage_size],
+        };
+        evaluator.init_positional_weights();
+        evaluator
+    }
+
+    /// Sets every configuration's weight to the sum of the values of the squares taken by Light,
+    /// minus those taken by Dark, divided by how many patterns the square belongs to.
+    fn init_positional_weights(&mut self) {
+        let mut coverage = [[0u8; BOARD_SIZE]; BOARD_SIZE];
+        for pattern in &self.patterns {
+            for instance in &pattern.instances {
+                for coord in instance {
+                    coverage[coord.get_row()][coord.get_col()] += 1;
+                }
+            }
+        }
+        for pattern in &self.patterns {
+            let cells = &pattern.instances[0];
+            for config in 0..pattern.get_size() {
+                let mut weight = 0.0;
+                let mut rest = config;
+                for &coord in cells.iter().rev() {
+                    let value = square_value(coord) as f32 / coverage[coord.get_row()][coord.get_col()] as f32;
+                    match rest % CELL_STATES {
+                        1 => weight -= value,
+                        2 => weight += value,
+                        _ => {}
+                    }
+                    rest /= CELL_STATES;
+                }
+                for stage in 0..self.stages {
+                    self.weights[stage * self.stage_size + pattern.offset + config] = weight;
+                }
+            }
+        }
+    }
+
+    /// Returns the number of game stages.
+    #[inline(always)]
+    pub fn get_stages(&self) -> usize {
+        self.stages
+    }
+
+    /// Returns the stage of the game the given turn belongs to.
+    #[inline(always)]
+    pub fn get_stage(&self, turn: &Turn) -> usize {
+        (turn.get_tempo() as usize).saturating_sub(4) * self.stages / (NUM_CELLS - 3)
+    }
+
+    /// Returns the weights, one table per stage.
+    #[inline(always)]
+    pub fn get_weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Returns the weights mutably, one table per stage.
+    #[inline(always)]
+    pub fn get_weights_mut(&mut self) -> &mut [f32] {
+        &mut self.weights
+    }
+
+    /// Returns the indexes of the weights which are summed to evaluate the given turn,
+    /// one for each instance of each pattern.
+    pub fn get_feature_indexes(&self, turn: &Turn) -> Vec<usize> {
+        let stage_offset = self.get_stage(turn) * self.stage_size;
+        let board = turn.get_board();
+        self.patterns.iter()
+            .flat_map(|pattern| pattern.instances.iter()
+                .map(move |instance| stage_offset + pattern.offset + pattern.get_index(board, instance)))
+            .collect()
+    }
+
+    /// Loads an evaluator from a weights file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<PatternEvaluator> {
+        PatternEvaluator::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Saves the evaluator to a weights file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads an evaluator in the binary weights format: magic bytes, then version, board size, stages
+    /// and number of weights per stage as little-endian `u32`s, then all weights as little-endian `f32`s.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<PatternEvaluator> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != WEIGHTS_MAGIC {
+            return Err(invalid("Not a weights file"));
+        }
+        if read_u32(reader)? != WEIGHTS_VERSION {
+            return Err(invalid("Unsupported weights file version"));
+        }
+        if read_u32(reader)? as usize != BOARD_SIZE {
+            return Err(invalid("Weights are for a different board size"));
+        }
+        let stages = read_u32(reader)? as usize;
+        if stages == 0 {
+            return Err(invalid("There has to be at least one stage"));
+        }
+        let mut evaluator = PatternEvaluator::new(stages);
+        if read_u32(reader)? as usize != evaluator.stage_size {
+            return Err(invalid("Weights are for different patterns"));
+        }
+        for weight in &mut evaluator.weights {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            *weight = f32::from_le_bytes(bytes);
+        }
+        Ok(evaluator)
+    }
+
+    /// Writes the evaluator in the binary weights format (see `read_from`).
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(WEIGHTS_MAGIC)?;
+        for &value in &[WEIGHTS_VERSION, BOARD_SIZE as u32, self.stages as u32, self.stage_size as u32] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for weight in &self.weights {
+            writer.write_all(&weight.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl IsEvaluator for PatternEvaluator {
+    #[inline(always)]
+    fn evaluate(&self, turn: &Turn) -> f32 {
+        self.get_feature_indexes(turn).into_iter().map(|index| self.weights[index]).sum()
+    }
+}
+
+/// Reads a little-endian `u32`.
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
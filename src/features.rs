@@ -0,0 +1,235 @@
+//! Implementation of positional features of a turn, to be used by evaluators and learning algorithms.
+//! Per-side features are given as `(dark, light)` pairs, as `Turn::get_score` does.
+
+use board::*;
+use turn::*;
+
+/// All the positional features of a turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features {
+    pub mobility: (u8, u8),
+    pub potential_mobility: (u8, u8),
+    pub frontier_disks: (u8, u8),
+    pub stable_disks: (u8, u8),
+    pub corners: (u8, u8),
+    pub x_squares: (u8, u8),
+    pub c_squares: (u8, u8),
+    pub odd_empty_regions: u8,
+    pub edges: [usize; 4],
+}
+
+/// How thoroughly stable disks are looked for. Both modes only give a lower bound: every disk they find is stable,
+/// but some stable disks may be missed (e.g. disks which no sequence of legal moves can flip, for lack of moves).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StabilityMode {
+    /// Iterates edge anchoring, full lines and lines blocked by stable disks until no more stable disks are found.
+    Extended,
+    /// Only looks for disks anchored to the edges through stable disks of the same side. It is faster,
+    /// but it misses disks which are stable because of full lines.
+    Conservative,
+}
+
+/// The four axes a disk can be flipped along, each given by one of its two directions.
+const AXES: [Direction; 4] = [Direction::North, Direction::NE, Direction::East, Direction::SE];
+
+/// Returns the side of the disk in the given cell, if the cell is on the board and is taken.
+#[inline(always)]
+fn side_at(board: &Board, coord: Coord) -> Option<::Side> {
+    board.get_cell(coord).ok().and_then(|cell| cell.map(|disk| disk.get_side()))
+}
+
+/// Returns whether the given cell is on the board and empty.
+#[inline(always)]
+fn is_empty_at(board: &Board, coord: Coord) -> bool {
+    board.is_empty(coord).unwrap_or(false)
+}
+
+/// Iterates over all the coordinates of the board, row by row.
+#[inline(always)]
+fn all_coords() -> impl Iterator<Item = Coord> {
+    (0..NUM_CELLS).map(|index| Coord::new(index / BOARD_SIZE, index % BOARD_SIZE))
+}
+
+/// Returns how far the given index is from the nearest border.
+#[inline(always)]
+fn distance_from_border(index: usize) -> usize {
+    index.min(BOARD_SIZE - 1 - index)
+}
+
+impl Turn {
+    /// Counts the disks of the given side which satisfy the given condition.
+    #[inline(always)]
+    fn count_disks<F: Fn(Coord) -> bool>(&self, side: ::Side, condition: F) -> u8 {
+        let board = self.get_board();
+        all_coords().filter(|&coord| side_at(board, coord) == Some(side) && condition(coord)).count() as u8
+    }
+
+    /// Returns the number of legal moves the given side would have, if it were its turn.
+    pub fn get_mobility(&self, side: ::Side) -> u8 {
+        all_coords().filter(|&coord| self.is_legal_for(coord, side)).count() as u8
+    }
+
+    /// Returns the number of empty cells next to at least one disk of the given side's opponent.
+    pub fn get_potential_mobility(&self, side: ::Side) -> u8 {
+        let board = self.get_board();
+        all_coords()
+            .filter(|&coord| is_empty_at(board, coord)
+                && DIRECTIONS.iter().any(|&dir| side_at(board, coord.step(dir)) == Some(side.opposite())))
+            .count() as u8
+    }
+
+    /// Returns the number of disks of the given side next to at least one empty cell.
+    pub fn get_frontier_disks(&self, side: ::Side) -> u8 {
+        let board = self.get_board();
+        self.count_disks(side, |coord| DIRECTIONS.iter().any(|&dir| is_empty_at(board, coord.step(dir))))
+    }
+
+    /// Returns the number of disks of the given side which can never be flipped again,
+    /// as found by `stable_disks` in extended mode.
+    pub fn get_stable_disks(&self, side: ::Side) -> u8 {
+        self.stable_disks(side, StabilityMode::Extended).len() as u8
+    }
+
+    /// Returns the disks of the given side which can never be flipped again, row by row.
+    /// A disk is stable if it cannot be flipped along any of the four axes, which is the case if
+    /// one of its neighbours along the axis is out of the board or is a stable disk of the same side.
+    /// In extended mode, the axis is also safe if the line is full,
+    /// or if both neighbours along the axis are stable disks of the other side.
+    pub fn stable_disks(&self, side: ::Side, mode: StabilityMode) -> Vec<Coord> {
+        let board = self.get_board();
+        let mut stable = [[false; BOARD_SIZE]; BOARD_SIZE];
+        let is_stable = |stable: &[[bool; BOARD_SIZE]; BOARD_SIZE], coord: Coord, side: ::Side| {
+            board.get_cell(coord).is_err() || (side_at(board, coord) == Some(side) && stable[coord.get_row()][coord.get_col()])
+        };
+        let is_full_line = |coord: Coord, dir: Direction| {
+            [dir, dir.opposite()].iter().all(|&dir| {
+                let mut next = coord.step(dir);
+                while let Ok(cell) = board.get_cell(next) {
+                    if cell.is_none() {
+                        return false;
+                    }
+                    next = next.step(dir);
+                }
+                true
+            })
+        };
+        // The stability of both sides' disks is computed, since in extended mode they depend on each other.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for coord in all_coords() {
+                let disk_side = match side_at(board, coord) {
+                    Some(disk_side) if !stable[coord.get_row()][coord.get_col()] => disk_side,
+                    _ => continue,
+                };
+                let is_safe_axis = |dir: Direction| {
+                    let (forward, backward) = (coord.step(dir), coord.step(dir.opposite()));
+                    is_stable(&stable, forward, disk_side) || is_stable(&stable, backward, disk_side)
+                        || (mode == StabilityMode::Extended
+                            && (is_full_line(coord, dir)
+                                || (side_at(board, forward) == Some(disk_side.opposite())
+                                    && side_at(board, backward) == Some(disk_side.opposite())
+                                    && is_stable(&stable, forward, disk_side.opposite())
+                                    && is_stable(&stable, backward, disk_side.opposite()))))
+                };
+                if AXES.iter().all(|&dir| is_safe_axis(dir)) {
+                    stable[coord.get_row()][coord.get_col()] = true;
+                    changed = true;
+                }
+            }
+        }
+        all_coords()
+            .filter(|&coord| stable[coord.get_row()][coord.get_col()] && side_at(board, coord) == Some(side))
+            .collect()
+    }
+
+    /// Returns the number of corners taken by the given side.
+    pub fn get_corner_occupancy(&self, side: ::Side) -> u8 {
+        self.count_disks(side, |coord| {
+            distance_from_border(coord.get_row()) == 0 && distance_from_border(coord.get_col()) == 0
+        })
+    }
+
+    /// Returns the number of X-squares (diagonally next to corners) taken by the given side.
+    pub fn get_x_square_occupancy(&self, side: ::Side) -> u8 {
+        self.count_disks(side, |coord| {
+            distance_from_border(coord.get_row()) == 1 && distance_from_border(coord.get_col()) == 1
+        })
+    }
+
+    /// Returns the number of C-squares (on the edges, next to corners) taken by the given side.
+    pub fn get_c_square_occupancy(&self, side: ::Side) -> u8 {
+        self.count_disks(side, |coord| {
+            matches!((distance_from_border(coord.get_row()), distance_from_border(coord.get_col())), (0, 1) | (1, 0))
+        })
+    }
+
+    /// Returns the sizes of the regions of connected (also diagonally) empty cells.
+    pub fn get_empty_regions(&self) -> Vec<u8> {
+        let board = self.get_board();
+        let mut visited = [[false; BOARD_SIZE]; BOARD_SIZE];
+        let mut regions = Vec::new();
+        for start in all_coords() {
+            if !is_empty_at(board, start) || visited[start.get_row()][start.get_col()] {
+                continue;
+            }
+            visited[start.get_row()][start.get_col()] = true;
+            let mut size = 0;
+            let mut stack = vec![start];
+            while let Some(coord) = stack.pop() {
+                size += 1;
+                for &dir in DIRECTIONS.iter() {
+                    let next = coord.step(dir);
+                    if is_empty_at(board, next) && !visited[next.get_row()][next.get_col()] {
+                        visited[next.get_row()][next.get_col()] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            regions.push(size);
+        }
+        regions
+    }
+
+    /// Returns the number of empty regions with an odd number of cells.
+    pub fn get_odd_empty_regions(&self) -> u8 {
+        self.get_empty_regions().into_iter().filter(|size| size % 2 == 1).count() as u8
+    }
+
+    /// Returns the configurations of the top, bottom, left and right edges (in this order).
+    /// Each is read from the lowest index to the highest as a base 3 number,
+    /// whose digits are 0 for an empty cell, 1 for a Dark disk and 2 for a Light disk.
+    pub fn get_edge_configurations(&self) -> [usize; 4] {
+        let board = self.get_board();
+        let last = BOARD_SIZE - 1;
+        let configuration = |coord: &dyn Fn(usize) -> Coord| {
+            (0..BOARD_SIZE).fold(0, |config, index| config * 3 + match side_at(board, coord(index)) {
+                None => 0,
+                Some(::Side::Dark) => 1,
+                Some(::Side::Light) => 2,
+            })
+        };
+        [
+            configuration(&|index| Coord::new(0, index)),
+            configuration(&|index| Coord::new(last, index)),
+            configuration(&|index| Coord::new(index, 0)),
+            configuration(&|index| Coord::new(index, last)),
+        ]
+    }
+
+    /// Computes all the positional features of the turn.
+    pub fn get_features(&self) -> Features {
+        let both = |feature: &dyn Fn(::Side) -> u8| (feature(::Side::Dark), feature(::Side::Light));
+        Features {
+            mobility: both(&|side| self.get_mobility(side)),
+            potential_mobility: both(&|side| self.get_potential_mobility(side)),
+            frontier_disks: both(&|side| self.get_frontier_disks(side)),
+            stable_disks: both(&|side| self.get_stable_disks(side)),
+            corners: both(&|side| self.get_corner_occupancy(side)),
+            x_squares: both(&|side| self.get_x_square_occupancy(side)),
+            c_squares: both(&|side| self.get_c_square_occupancy(side)),
+            odd_empty_regions: self.get_odd_empty_regions(),
+            edges: self.get_edge_configurations(),
+        }
+    }
+}
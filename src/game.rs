@@ -1,45 +1,273 @@
 //! Implementation of a complete Reversi match.
 
+use std::cmp::Ordering;
+use std::future::Future;
 use std::marker::{PhantomData, Sized};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use board::*;
 use turn::*;
+use clock::*;
 use ::Result;
 
 
 pub enum PlayerAction<A> {
     Move(Coord),
     Undo,
+    /// The player gives up the game.
+    Resign,
     Other(A),
 }
 
+/// The possible outcomes of a game.
+/// Where a winner is given, it is the side which won the game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameResult {
+    /// The game was played to the end. The final disk count is given as `(dark, light)`.
+    WinByDisks(::Side, (u8, u8)),
+    /// The loser resigned.
+    WinByResignation(::Side),
+    /// The loser ran out of time.
+    WinByTimeout(::Side),
+    /// The loser forfeited the game by making an illegal move.
+    WinByForfeit(::Side),
+    /// The game was played to the end with equal disk count.
+    Draw,
+    /// The players agreed to a draw.
+    DrawByAgreement,
+}
+
+impl GameResult {
+    /// Returns the result of a game ended at the given turn, or `None` if the game is still running.
+    /// As by WOF rules, empty squares are awarded to the winner.
+    pub fn from_final_turn(turn: &Turn) -> Option<GameResult> {
+        if !turn.is_end_state() {
+            return None;
+        }
+        let (dark, light) = turn.get_score();
+        let empty = NUM_CELLS as u8 - dark - light;
+        Some(match dark.cmp(&light) {
+            Ordering::Greater => GameResult::WinByDisks(::Side::Dark, (dark + empty, light)),
+            Ordering::Less => GameResult::WinByDisks(::Side::Light, (dark, light + empty)),
+            Ordering::Equal => GameResult::Draw,
+        })
+    }
+
+    /// Returns the winning side, or `None` if the game was drawn.
+    pub fn get_winner(&self) -> Option<::Side> {
+        match *self {
+            GameResult::WinByDisks(side, _)
+            | GameResult::WinByResignation(side)
+            | GameResult::WinByTimeout(side)
+            | GameResult::WinByForfeit(side) => Some(side),
+            GameResult::Draw | GameResult::DrawByAgreement => None,
+        }
+    }
+}
+
 /// Being able to make moves is the trait characterizing players.
 pub trait IsPlayer<A> {
     fn make_move(&self, turn: &Turn) -> Result<PlayerAction<A>>;
+
+    /// Makes a move knowing how much time is left on the player's clock.
+    /// By default, the time left is ignored.
+    fn make_timed_move(&self, turn: &Turn, _time_left: Duration) -> Result<PlayerAction<A>> {
+        self.make_move(turn)
+    }
+}
+
+/// Players with an internal state (such as an opening book position, transposition tables or RNGs),
+/// which is updated as they make moves and as they are notified about the progress of the game.
+/// Every `&P` with `P: IsPlayer` is a stateful player which ignores notifications.
+pub trait IsStatefulPlayer<A> {
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>>;
+
+    /// Makes a move knowing how much time is left on the player's clock.
+    /// By default, the time left is ignored.
+    fn make_timed_move(&mut self, turn: &Turn, _time_left: Duration) -> Result<PlayerAction<A>> {
+        self.make_move(turn)
+    }
+
+    /// Notifies the player that the game is starting, and which side it is playing.
+    fn on_game_start(&mut self, _side: ::Side, _turn: &Turn) {}
+
+    /// Notifies the player of the opponent's move, and of the resulting turn.
+    fn on_opponent_move(&mut self, _coord: Coord, _turn: &Turn) {}
+
+    /// Notifies the player that some moves have been undone, and of the resulting turn.
+    fn on_undo(&mut self, _turn: &Turn) {}
+
+    /// Notifies the player that the game is over.
+    fn on_game_end(&mut self, _result: &GameResult) {}
+
+    /// Invites the player to ponder on the given turn, where the opponent is to move,
+    /// after the player has moved in a game with pondering enabled.
+    /// See `ponder::Ponder` for running the search in the background.
+    fn start_pondering(&mut self, _turn: &Turn) {}
+}
+
+impl<'p, A, P: 'p + ?Sized + IsPlayer<A>> IsStatefulPlayer<A> for &'p P {
+    #[inline(always)]
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>> {
+        IsPlayer::make_move(*self, turn)
+    }
+
+    #[inline(always)]
+    fn make_timed_move(&mut self, turn: &Turn, time_left: Duration) -> Result<PlayerAction<A>> {
+        IsPlayer::make_timed_move(*self, turn, time_left)
+    }
+}
+
+impl<'p, A, P: 'p + ?Sized + IsStatefulPlayer<A>> IsStatefulPlayer<A> for &'p mut P {
+    #[inline(always)]
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>> {
+        (**self).make_move(turn)
+    }
+
+    #[inline(always)]
+    fn make_timed_move(&mut self, turn: &Turn, time_left: Duration) -> Result<PlayerAction<A>> {
+        (**self).make_timed_move(turn, time_left)
+    }
+
+    #[inline(always)]
+    fn on_game_start(&mut self, side: ::Side, turn: &Turn) {
+        (**self).on_game_start(side, turn)
+    }
+
+    #[inline(always)]
+    fn on_opponent_move(&mut self, coord: Coord, turn: &Turn) {
+        (**self).on_opponent_move(coord, turn)
+    }
+
+    #[inline(always)]
+    fn on_undo(&mut self, turn: &Turn) {
+        (**self).on_undo(turn)
+    }
+
+    #[inline(always)]
+    fn on_game_end(&mut self, result: &GameResult) {
+        (**self).on_game_end(result)
+    }
+
+    #[inline(always)]
+    fn start_pondering(&mut self, turn: &Turn) {
+        (**self).start_pondering(turn)
+    }
+}
+
+/// The future returned by asynchronous players, resolving to the player's action.
+pub type PlayerFuture<'p, A> = Pin<Box<dyn Future<Output = Result<PlayerAction<A>>> + Send + 'p>>;
+
+/// Asynchronous counterpart of `IsPlayer`, for players which should not block the thread running the game
+/// (such as network players, GUI-driven humans or long engine searches).
+pub trait IsAsyncPlayer<A> {
+    fn make_move<'p>(&'p self, turn: Turn) -> PlayerFuture<'p, A>;
+
+    /// Makes a move knowing how much time is left on the player's clock.
+    /// By default, the time left is ignored.
+    fn make_timed_move<'p>(&'p self, turn: Turn, _time_left: Duration) -> PlayerFuture<'p, A> {
+        self.make_move(turn)
+    }
+}
+
+/// How a game deals with a player attempting an illegal move.
+/// In any case, the game's history is never modified by an illegal move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IllegalMovePolicy {
+    /// The move is rejected and the error is returned, so that the player can be asked again.
+    Reject,
+    /// The player is asked again, up to the given number of times, before rejecting the move.
+    Retry(u8),
+    /// The player loses the game by forfeit.
+    Forfeit,
+    /// Panics. Meant for testing players in strict mode.
+    Panic,
 }
 
 /// A game is given by a list of past turns (with the successive move), a current turn, and the two players.
-pub struct Game<'a, A, D: 'a + ?Sized + IsPlayer<A>, L: 'a + ?Sized + IsPlayer<A>> {
+/// Players can be either synchronous (implementing `IsStatefulPlayer`, as do references to `IsPlayer`s)
+/// or references to asynchronous players (implementing `IsAsyncPlayer`).
+#[derive(Clone)]
+pub struct Game<A, D, L> {
     current_turn: Turn,
     turns_history: Vec<(Turn, Coord)>,
-    dark:  &'a D,
-    light: &'a L,
+    dark:  D,
+    light: L,
+    started: bool,
+    ended: bool,
+    pondering: bool,
+    dark_clock: Option<Clock>,
+    light_clock: Option<Clock>,
+    result: Option<GameResult>,
+    illegal_move_policy: IllegalMovePolicy,
     phantom: PhantomData<A>
 }
 
-impl<'a, A, D: 'a + ?Sized + IsPlayer<A>, L: 'a + ?Sized + IsPlayer<A>> Game<'a, A, D, L> {
+impl<A, D, L> Game<A, D, L> {
 
     /// Creates a new game, with first turn already set and empty turns' history.
     /// It requires the two players as input.
-    pub fn new(dark: &'a D, light: &'a L) -> Game<'a, A, D, L> where D: IsPlayer<A>, L: IsPlayer<A> {
+    pub fn new(dark: D, light: L) -> Game<A, D, L> {
         Game {
             current_turn: Turn::first_turn(),
             turns_history: vec![],
             dark: dark,
             light: light,
+            started: false,
+            ended: false,
+            pondering: false,
+            dark_clock: None,
+            light_clock: None,
+            result: None,
+            illegal_move_policy: IllegalMovePolicy::Reject,
             phantom: PhantomData,
         }
     }
 
+    /// Enables or disables pondering: if enabled, players are invited to ponder after each of their moves.
+    #[inline(always)]
+    pub fn set_pondering(&mut self, pondering: bool) {
+        self.pondering = pondering;
+    }
+
+    /// Sets how illegal moves are dealt with. By default, they are rejected.
+    #[inline(always)]
+    pub fn set_illegal_move_policy(&mut self, policy: IllegalMovePolicy) {
+        self.illegal_move_policy = policy;
+    }
+
+    /// Sets the time controls for the two players, resetting their clocks.
+    pub fn set_time_controls(&mut self, dark: TimeControl, light: TimeControl) {
+        self.dark_clock = Some(Clock::new(dark));
+        self.light_clock = Some(Clock::new(light));
+    }
+
+    /// Gets the clock of the given side, if the game is timed.
+    #[inline(always)]
+    pub fn get_clock(&self, side: ::Side) -> Option<&Clock> {
+        match side {
+            ::Side::Dark  => self.dark_clock.as_ref(),
+            ::Side::Light => self.light_clock.as_ref(),
+        }
+    }
+
+    /// Returns the game's result, or `None` if the game is still running.
+    #[inline(always)]
+    pub fn get_result(&self) -> Option<GameResult> {
+        self.result.or_else(|| GameResult::from_final_turn(&self.current_turn))
+    }
+
+    /// Ends the game with a draw agreed upon by the players.
+    pub fn agree_draw(&mut self) -> Result<()> {
+        if self.is_endgame() {
+            Err(::ReversiError::EndedGame(self.current_turn))
+        } else {
+            self.result = Some(GameResult::DrawByAgreement);
+            Ok(())
+        }
+    }
+
     /// Gets the current turn.
     #[inline(always)]
     pub fn get_current_turn(&self) -> &Turn {
@@ -58,10 +286,10 @@ impl<'a, A, D: 'a + ?Sized + IsPlayer<A>, L: 'a + ?Sized + IsPlayer<A>> Game<'a,
         self.current_turn.get_state()
     }
 
-    /// Returns true if the game is ended.
+    /// Returns true if the game is ended, either regularly or by resignation, timeout, forfeit or agreement.
     #[inline(always)]
     pub fn is_endgame(&self) -> bool {
-        self.current_turn.is_end_state()
+        self.current_turn.is_end_state() || self.result.is_some()
     }
 
     /// Gets the score of the current turn.
@@ -76,35 +304,107 @@ impl<'a, A, D: 'a + ?Sized + IsPlayer<A>, L: 'a + ?Sized + IsPlayer<A>> Game<'a,
         self.current_turn.get_score_diff()
     }
 
+    /// Returns the moves played so far, from the first turn.
+    pub fn get_moves(&self) -> Vec<Coord> {
+        self.turns_history.iter().map(|&(_, coord)| coord).collect()
+    }
 
-    /// It has the correct player return an action and applies its effects.
+
+    /// Returns the side which has to play next, or an error if the game is ended.
     #[inline(always)]
-    pub fn play_turn(&mut self) -> Result<PlayerAction<A>> {
-        let action = match self.current_turn.get_state() {
-            None => return Err(::ReversiError::EndedGame(self.current_turn)),
-            Some(::Side::Dark)  => self.dark.make_move(&self.current_turn)?,
-            Some(::Side::Light) => self.light.make_move(&self.current_turn)?,
+    fn get_running_side(&self) -> Result<::Side> {
+        match self.result {
+            Some(_) => Err(::ReversiError::EndedGame(self.current_turn)),
+            None => self.current_turn.get_state().ok_or(::ReversiError::EndedGame(self.current_turn)),
+        }
+    }
+
+    /// Passes on the action returned by the given side. If its player is unavailable, it loses the game by forfeit.
+    fn check_available(&mut self, side: ::Side, action: Result<PlayerAction<A>>) -> Result<PlayerAction<A>> {
+        if let Err(::ReversiError::PlayerUnavailable(_)) = action {
+            self.result = Some(GameResult::WinByForfeit(side.opposite()));
+        }
+        action
+    }
+
+    /// Returns how many times a player can be asked again after an illegal move.
+    #[inline(always)]
+    fn get_retries(&self) -> u8 {
+        match self.illegal_move_policy {
+            IllegalMovePolicy::Retry(retries) => retries,
+            _ => 0,
+        }
+    }
+
+    /// Charges the time spent by the given side to its clock, if the game is timed.
+    /// If the side runs out of time, it loses the game and `ReversiError::TimeOut` is returned.
+    /// Clocks are charged by `play_turn`; games driven by `make_move` have to charge them directly.
+    pub fn charge_clock(&mut self, side: ::Side, elapsed: Duration) -> Result<()> {
+        let clock = match side {
+            ::Side::Dark  => self.dark_clock.as_mut(),
+            ::Side::Light => self.light_clock.as_mut(),
         };
+        if let Some(clock) = clock {
+            if !clock.charge(elapsed) {
+                self.result = Some(GameResult::WinByTimeout(side.opposite()));
+                return Err(::ReversiError::TimeOut(side));
+            }
+        }
+        Ok(())
+    }
 
-        match action {
+    /// Applies the effects of the action returned by the given side.
+    /// It returns `Ok(false)` if the player made an illegal move and has to be asked again.
+    fn apply_action(&mut self, side: ::Side, action: &PlayerAction<A>, retries: &mut u8) -> Result<bool> {
+        match *action {
             // If that move is legal, it is applied and the turns' history is updated.
-            PlayerAction::Move(coord) => self.make_move(coord)?,
+            PlayerAction::Move(coord) => {
+                if let Err(err) = self.make_move(coord) {
+                    match self.illegal_move_policy {
+                        IllegalMovePolicy::Retry(_) if *retries > 0 => {
+                            *retries -= 1;
+                            return Ok(false);
+                        }
+                        IllegalMovePolicy::Forfeit => {
+                            self.result = Some(GameResult::WinByForfeit(side.opposite()));
+                        }
+                        IllegalMovePolicy::Panic => panic!("{:?} made an illegal move: {}", side, err),
+                        _ => {}
+                    }
+                    return Err(err);
+                }
+            }
             PlayerAction::Undo => self.undo()?,
+            PlayerAction::Resign => self.result = Some(GameResult::WinByResignation(side.opposite())),
             _ => {}
         }
-
-        Ok(action)
+        Ok(true)
     }
 
     /// A move (given by `coord`) is applied. If that move is legal, game's history is updated.
+    /// If it is not, neither the current turn nor the history are modified.
+    /// Moves can be applied directly, without asking the players, to set up an opening or to replay a game:
+    /// players are not notified about them.
     #[inline(always)]
-    fn make_move(&mut self, coord: Coord) -> Result<()> {
+    pub fn make_move(&mut self, coord: Coord) -> Result<()> {
+        if self.result.is_some() {
+            return Err(::ReversiError::EndedGame(self.current_turn));
+        }
+        let mut next_turn = self.current_turn;
+        next_turn.make_move(coord)?;
         self.turns_history.push((self.current_turn, coord));
-        self.current_turn.make_move(coord)
+        self.current_turn = next_turn;
+        Ok(())
     }
 
-    /// Undo last move(s) till the player asking for undoing can play again.
-    fn undo(&mut self) -> Result<()> {
+    /// Undo last move(s) till the player asking for undoing (the side to move) can play again.
+    /// If the game is played to the end, the last move is undone along with the previous ones of the same side.
+    /// Undoing can also be done directly, without asking the players: players are not notified about it.
+    /// Games decided otherwise than by the disks (by timeout, resignation, forfeit or agreement) cannot be undone.
+    pub fn undo(&mut self) -> Result<()> {
+        if self.result.is_some() {
+            return Err(::ReversiError::EndedGame(self.current_turn));
+        }
         let backup = self.turns_history.clone();
         match self.get_current_state() {
             None => {
@@ -132,3 +432,137 @@ impl<'a, A, D: 'a + ?Sized + IsPlayer<A>, L: 'a + ?Sized + IsPlayer<A>> Game<'a,
         }
     }
 }
+
+impl<A, D: IsStatefulPlayer<A>, L: IsStatefulPlayer<A>> Game<A, D, L> {
+
+    /// It has the correct player return an action and applies its effects.
+    /// If the game is timed, the time spent by the player is charged to its clock,
+    /// and if it runs out of time the game is lost and `ReversiError::TimeOut` is returned.
+    /// If the player returns `ReversiError::PlayerUnavailable`, the game is lost by forfeit and the error is returned.
+    /// Illegal moves are dealt with according to the game's `IllegalMovePolicy`.
+    /// Players are notified about the game's start and end, and about each other's moves.
+    #[inline(always)]
+    pub fn play_turn(&mut self) -> Result<PlayerAction<A>> {
+        if !self.started {
+            self.started = true;
+            self.dark.on_game_start(::Side::Dark, &self.current_turn);
+            self.light.on_game_start(::Side::Light, &self.current_turn);
+        }
+        let outcome = self.get_running_side().and_then(|side| self.play_side(side));
+        if !self.ended {
+            if let Some(result) = self.get_result() {
+                self.ended = true;
+                self.dark.on_game_end(&result);
+                self.light.on_game_end(&result);
+            }
+        }
+        outcome
+    }
+
+    /// Has the given side play its turn and notifies the players of its effects.
+    fn play_side(&mut self, side: ::Side) -> Result<PlayerAction<A>> {
+        let mut retries = self.get_retries();
+        loop {
+            let action = self.ask_player(side)?;
+            if self.apply_action(side, &action, &mut retries)? {
+                match action {
+                    PlayerAction::Move(coord) => {
+                        match side {
+                            ::Side::Dark  => self.light.on_opponent_move(coord, &self.current_turn),
+                            ::Side::Light => self.dark.on_opponent_move(coord, &self.current_turn),
+                        }
+                        if self.pondering && self.current_turn.get_state() == Some(side.opposite()) {
+                            match side {
+                                ::Side::Dark  => self.dark.start_pondering(&self.current_turn),
+                                ::Side::Light => self.light.start_pondering(&self.current_turn),
+                            }
+                        }
+                    }
+                    PlayerAction::Undo => {
+                        self.dark.on_undo(&self.current_turn);
+                        self.light.on_undo(&self.current_turn);
+                    }
+                    _ => {}
+                }
+                return Ok(action);
+            }
+        }
+    }
+
+    /// Has the player of the given side return an action, charging the time spent to its clock.
+    fn ask_player(&mut self, side: ::Side) -> Result<PlayerAction<A>> {
+        let time_left = self.get_clock(side).map(|clock| clock.get_time_left());
+        let start = Instant::now();
+        let action = match (side, time_left) {
+            (::Side::Dark, None)             => self.dark.make_move(&self.current_turn),
+            (::Side::Light, None)            => self.light.make_move(&self.current_turn),
+            (::Side::Dark, Some(time_left))  => self.dark.make_timed_move(&self.current_turn, time_left),
+            (::Side::Light, Some(time_left)) => self.light.make_timed_move(&self.current_turn, time_left),
+        };
+        self.charge_clock(side, start.elapsed())?;
+        self.check_available(side, action)
+    }
+}
+
+impl<'a, A, D: 'a + ?Sized + IsAsyncPlayer<A>, L: 'a + ?Sized + IsAsyncPlayer<A>> Game<A, &'a D, &'a L> {
+
+    /// Asynchronous counterpart of `play_turn`, to be used with asynchronous players.
+    /// The returned future does not depend on any specific executor.
+    /// Asynchronous players are not notified about the progress of the game.
+    pub fn play_turn_async<'g>(&'g mut self) -> PlayTurn<'g, 'a, A, D, L> {
+        let side = self.get_running_side();
+        let retries = self.get_retries();
+        PlayTurn {
+            game: self,
+            side,
+            retries,
+            pending: None,
+        }
+    }
+}
+
+/// The future returned by `Game::play_turn_async`, resolving to the action of the player.
+pub struct PlayTurn<'g, 'a: 'g, A: 'g, D: 'a + ?Sized, L: 'a + ?Sized> {
+    game: &'g mut Game<A, &'a D, &'a L>,
+    side: Result<::Side>,
+    retries: u8,
+    pending: Option<(PlayerFuture<'a, A>, Instant)>,
+}
+
+impl<'g, 'a: 'g, A: 'g, D: 'a + ?Sized + IsAsyncPlayer<A>, L: 'a + ?Sized + IsAsyncPlayer<A>> Future for PlayTurn<'g, 'a, A, D, L> {
+    type Output = Result<PlayerAction<A>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let side = this.side?;
+        loop {
+            if this.pending.is_none() {
+                let game = &*this.game;
+                let turn = game.current_turn;
+                let (dark, light) = (game.dark, game.light);
+                let future = match (side, game.get_clock(side).map(|clock| clock.get_time_left())) {
+                    (::Side::Dark, None)             => dark.make_move(turn),
+                    (::Side::Light, None)            => light.make_move(turn),
+                    (::Side::Dark, Some(time_left))  => dark.make_timed_move(turn, time_left),
+                    (::Side::Light, Some(time_left)) => light.make_timed_move(turn, time_left),
+                };
+                this.pending = Some((future, Instant::now()));
+            }
+            let poll = match this.pending {
+                Some((ref mut future, _)) => future.as_mut().poll(cx),
+                None => unreachable!("A move has just been requested!"),
+            };
+            let action = match poll {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(action) => {
+                    let (_, start) = this.pending.take().expect("A move has just been requested!");
+                    this.game.charge_clock(side, start.elapsed())?;
+                    this.game.check_available(side, action)?
+                }
+            };
+            if this.game.apply_action(side, &action, &mut this.retries)? {
+                return Poll::Ready(Ok(action));
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Implementation of the parsing of games in the Generic Game Format (GGF), as used by NBoard and game servers.
+
+use board::*;
+use turn::*;
+use ::Result;
+
+/// A game read from GGF: its starting turn, the side to move in it (as given, even if that side has to pass)
+/// and its moves, where `None` stands for a pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgfGame {
+    pub start: Turn,
+    pub side: ::Side,
+    pub moves: Vec<Option<Coord>>,
+}
+
+impl GgfGame {
+    /// Returns the turn reached after all the moves (passes are implied by `Turn` and hence skipped).
+    pub fn get_current_turn(&self) -> Result<Turn> {
+        let mut turn = self.start;
+        for &coord in self.moves.iter().flatten() {
+            turn.make_move(coord)?;
+        }
+        Ok(turn)
+    }
+
+    /// Returns the side to move according to the game's moves, passes included.
+    pub fn get_side_to_move(&self) -> ::Side {
+        match self.moves.len() % 2 {
+            0 => self.side,
+            _ => self.side.opposite(),
+        }
+    }
+}
+
+/// Parses a GGF move: a coordinate such as `F5` or `PA` for a pass, optionally followed by `/eval/time`.
+pub fn parse_move(ggf_move: &str) -> Result<Option<Coord>> {
+    let coord = ggf_move.split('/').next().unwrap_or("").trim();
+    if coord.eq_ignore_ascii_case("pa") || coord.eq_ignore_ascii_case("pass") {
+        Ok(None)
+    } else {
+        coord.parse().map(Some)
+    }
+}
+
+/// Parses a game in GGF, such as `(;GM[Othello]BO[8 ---------------------------O*------*O--------------------------- *]B[F5]W[F6];)`.
+/// The board is given by `BO`, with `*` for Dark (Black) disks, `O` for Light (White) disks and `-` for empty cells,
+/// followed by the side to move; moves are given by `B` and `W` tags. Other tags are ignored.
+pub fn parse_game(ggf: &str) -> Result<GgfGame> {
+    let mut start = (Turn::first_turn(), ::Side::Dark);
+    let mut moves = Vec::new();
+    for (tag, value) in tags(ggf)? {
+        match tag {
+            "BO" => start = parse_board(value)?,
+            "B" | "W" => moves.push(parse_move(value)?),
+            _ => {}
+        }
+    }
+    Ok(GgfGame {
+        start: start.0,
+        side: start.1,
+        moves,
+    })
+}
+
+/// Returns the value of the first tag with the given name in a GGF string, such as `PB` for the Dark player.
+pub fn get_tag<'a>(ggf: &'a str, name: &str) -> Result<Option<&'a str>> {
+    Ok(tags(ggf)?.into_iter().find(|&(tag, _)| tag == name).map(|(_, value)| value))
+}
+
+/// Splits a GGF string into its tags and their values.
+fn tags(ggf: &str) -> Result<Vec<(&str, &str)>> {
+    let mut tags = Vec::new();
+    let mut rest = ggf;
+    while let Some(open) = rest.find('[') {
+        let name_start = rest[..open].rfind(|c: char| !c.is_ascii_uppercase()).map_or(0, |index| index + 1);
+        let close = rest[open..].find(']').ok_or(::ReversiError::InvalidNotation)? + open;
+        tags.push((&rest[name_start..open], &rest[open + 1..close]));
+        rest = &rest[close + 1..];
+    }
+    Ok(tags)
+}
+
+/// Parses a GGF board: its size, its cells and the side to move.
+fn parse_board(value: &str) -> Result<(Turn, ::Side)> {
+    let mut tokens = value.split_whitespace();
+    if tokens.next() != Some(&BOARD_SIZE.to_string()[..]) {
+        return Err(::ReversiError::InvalidNotation);
+    }
+    let symbols: Vec<char> = tokens.flat_map(|token| token.chars()).collect();
+    if symbols.len() != NUM_CELLS + 1 {
+        return Err(::ReversiError::InvalidNotation);
+    }
+    let mut cells = [[None; BOARD_SIZE]; BOARD_SIZE];
+    for (index, &symbol) in symbols[..NUM_CELLS].iter().enumerate() {
+        cells[index / BOARD_SIZE][index % BOARD_SIZE] = match symbol {
+            '*' => Some(Disk::new(::Side::Dark)),
+            'O' => Some(Disk::new(::Side::Light)),
+            '-' => None,
+            _ => return Err(::ReversiError::InvalidNotation),
+        };
+    }
+    let side = match symbols[NUM_CELLS] {
+        '*' => ::Side::Dark,
+        'O' => ::Side::Light,
+        _ => return Err(::ReversiError::InvalidNotation),
+    };
+    Ok((Turn::new(Board::new(cells), side), side))
+}
@@ -10,9 +10,28 @@
 #![cfg_attr(feature="clippy", feature(plugin))]
 #![cfg_attr(feature="clippy", plugin(clippy))]
 
+extern crate rand;
+
 pub mod board;
 pub mod turn;
 pub mod game;
+pub mod clock;
+pub mod ponder;
+pub mod mcts;
+pub mod eval;
+pub mod search;
+pub mod train;
+pub mod features;
+pub mod book;
+pub mod engine;
+pub mod selfplay;
+pub mod arena;
+pub mod tournament;
+pub mod rating;
+pub mod ggf;
+pub mod websocket;
+pub mod server;
+pub mod remote;
 
 use std::fmt;
 use board::{Coord, Direction};
@@ -37,6 +56,12 @@ pub enum ReversiError {
     EndedGame(turn::Turn),
     /// Undoing a turn is not possible
     NoUndo,
+    /// The given side has run out of time.
+    TimeOut(Side),
+    /// A turn or a move written in an unknown notation could not be parsed.
+    InvalidNotation,
+    /// The player of the given side could not be reached (e.g. a remote player which disconnected or did not answer).
+    PlayerUnavailable(Side),
 }
 
 /// Aliasing given by taking `ReversiError` as standard error value.
@@ -53,12 +78,15 @@ impl fmt::Display for ReversiError {
             ReversiError::EndedGame(game) => write!(f, "The game is already ended:\n {:?}", game),
             ReversiError::EmptyCell(coord) => write!(f, "The cell you want is empty: {:?}", coord),
             ReversiError::NoUndo => write!(f, "Undoing is not possible!"),
+            ReversiError::TimeOut(side) => write!(f, "{:?} has run out of time", side),
+            ReversiError::InvalidNotation => write!(f, "Invalid notation"),
+            ReversiError::PlayerUnavailable(side) => write!(f, "The {:?} player is unavailable", side),
         }
     }
 }
 
 /// There are two sides in Reversi: `Dark` and `Light`
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Side {
     Dark,
     Light,
@@ -73,3 +101,13 @@ impl Side {
         }
     }
 }
+
+/// Sides are written by their name, `Dark` or `Light`.
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Side::Dark  => write!(f, "Dark"),
+            Side::Light => write!(f, "Light"),
+        }
+    }
+}
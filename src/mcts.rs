@@ -0,0 +1,299 @@
+//! Implementation of a Monte Carlo Tree Search player, using the UCT selection rule.
+
+use std::f64;
+use std::time::{Duration, Instant};
+use rand::{self, Rng, SeedableRng, XorShiftRng};
+use board::*;
+use turn::*;
+use game::*;
+use eval::square_value;
+use ::Result;
+
+/// The default exploration constant, that is, the theoretical value `sqrt(2)`.
+pub const DEFAULT_EXPLORATION: f64 = f64::consts::SQRT_2;
+
+/// How the games are played out from the leaves of the search tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Playout {
+    /// Moves are chosen uniformly at random.
+    Random,
+    /// Moves on better squares (corners first, squares next to corners last) are chosen with higher probability.
+    Heuristic,
+}
+
+/// When the search has to stop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MctsLimit {
+    /// The search stops after the given number of iterations.
+    Iterations(u32),
+    /// The search stops after the given amount of time.
+    Time(Duration),
+}
+
+/// A node of the search tree, storing the statistics of the move leading to it.
+#[derive(Clone)]
+struct Node {
+    turn: Turn,
+    coord: Option<Coord>,
+    mover: Option<::Side>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Coord>,
+    visits: u32,
+    wins: f64,
+}
+
+impl Node {
+    fn new(turn: Turn, coord: Option<Coord>, mover: Option<::Side>, parent: Option<usize>) -> Node {
+        Node {
+            turn,
+            coord,
+            mover,
+            parent,
+            children: Vec::new(),
+            untried: turn.get_legal_moves(),
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+/// A search tree, stored as an arena of nodes with the root at index 0.
+#[derive(Clone)]
+struct Tree(Vec<Node>);
+
+impl Tree {
+    fn new(turn: Turn) -> Tree {
+        Tree(vec![Node::new(turn, None, None, None)])
+    }
+
+    /// Returns the subtree whose root is the (at most two levels deep) node corresponding to the given turn, if any.
+    fn subtree(&self, turn: &Turn) -> Option<Tree> {
+        let root = &self.0[0];
+        let found = if root.turn == *turn {
+            Some(0)
+        } else {
+            root.children.iter()
+                .flat_map(|&child| Some(child).into_iter().chain(self.0[child].children.iter().cloned()))
+                .find(|&index| self.0[index].turn == *turn)
+        };
+        found.map(|index| {
+            let mut nodes: Vec<Node> = Vec::new();
+            let mut stack = vec![(index, None)];
+            while let Some((old_index, new_parent)) = stack.pop() {
+                let new_index = nodes.len();
+                let mut node = self.0[old_index].clone();
+                node.parent = new_parent;
+                node.children.clear();
+                if let Some(parent) = new_parent {
+                    nodes[parent].children.push(new_index);
+                }
+                stack.extend(self.0[old_index].children.iter().map(|&child| (child, Some(new_index))));
+                nodes.push(node);
+            }
+            nodes[0].coord = None;
+            nodes[0].mover = None;
+            Tree(nodes)
+        })
+    }
+
+    /// Returns the statistics of every move of the root: its number of visits and its estimated winning probability.
+    fn root_moves(&self) -> Vec<(Coord, u32, f64)> {
+        self.0[0].children.iter()
+            .map(|&child| &self.0[child])
+            .map(|node| (node.coord.expect("Only the root has no move"), node.visits, node.wins / node.visits as f64))
+            .collect()
+    }
+}
+
+/// Returns the most visited of the given moves, with its estimated winning probability.
+fn most_visited(moves: Vec<(Coord, u32, f64)>) -> Option<(Coord, f64)> {
+    moves.into_iter()
+        .max_by_key(|&(_, visits, _)| visits)
+        .map(|(coord, _, win_rate)| (coord, win_rate))
+}
+
+/// A player choosing its moves by Monte Carlo Tree Search.
+/// As a stateful player, it reuses the search tree between its moves.
+pub struct MctsPlayer {
+    limit: MctsLimit,
+    exploration: f64,
+    playout: Playout,
+    rng: XorShiftRng,
+    tree: Option<Tree>,
+}
+
+impl MctsPlayer {
+    /// Creates a new player searching until the given limit, with default exploration constant and random playouts.
+    /// A limit of zero iterations is rejected, since no move could be chosen.
+    pub fn new(limit: MctsLimit) -> MctsPlayer {
+        assert!(limit != MctsLimit::Iterations(0), "The search needs at least one iteration");
+        MctsPlayer {
+            limit,
+            exploration: DEFAULT_EXPLORATION,
+            playout: Playout::Random,
+            rng: rand::weak_rng(),
+            tree: None,
+        }
+    }
+
+    /// Sets the exploration constant of the UCT rule.
+    #[inline(always)]
+    pub fn set_exploration(&mut self, exploration: f64) {
+        self.exploration = exploration;
+    }
+
+    /// Sets how games are played out.
+    #[inline(always)]
+    pub fn set_playout(&mut self, playout: Playout) {
+        self.playout = playout;
+    }
+
+    /// Seeds the player's random number generator, to make its searches reproducible.
+    pub fn set_seed(&mut self, seed: [u32; 4]) {
+        self.rng = XorShiftRng::from_seed(seed);
+    }
+
+    /// Searches the given turn, reusing the previous search tree when possible.
+    /// It returns the best move together with its estimated winning probability.
+    pub fn search(&mut self, turn: &Turn) -> Option<(Coord, f64)> {
+        most_visited(self.analyse(turn))
+    }
+
+    /// Searches the given turn as `search` does, returning the statistics of every move of the tree's root:
+    /// its number of visits and its estimated winning probability.
+    pub fn analyse(&mut self, turn: &Turn) -> Vec<(Coord, u32, f64)> {
+        let tree = self.tree.take()
+            .and_then(|tree| tree.subtree(turn))
+            .unwrap_or_else(|| Tree::new(*turn));
+        let mut rng = self.rng.clone();
+        let tree = self.run(tree, &mut rng);
+        self.rng = rng;
+        let moves = tree.root_moves();
+        self.tree = Some(tree);
+        moves
+    }
+
+    /// Forgets the search tree, so that the next search starts from scratch.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.tree = None;
+    }
+
+    /// Runs the search on the given tree until the limit is reached.
+    fn run<R: Rng>(&self, mut tree: Tree, rng: &mut R) -> Tree {
+        let start = Instant::now();
+        let mut iterations = 0;
+        while match self.limit {
+            MctsLimit::Iterations(limit) => iterations < limit,
+            MctsLimit::Time(limit) => iterations == 0 || start.elapsed() < limit,
+        } {
+            self.iterate(&mut tree, rng);
+            iterations += 1;
+        }
+        tree
+    }
+
+    /// Performs one iteration of the search: selection, expansion, playout and backpropagation.
+    fn iterate<R: Rng>(&self, tree: &mut Tree, rng: &mut R) {
+        let nodes = &mut tree.0;
+        // Selection
+        let mut index = 0;
+        while nodes[index].untried.is_empty() && !nodes[index].children.is_empty() {
+            let log_visits = (nodes[index].visits as f64).ln();
+            index = *nodes[index].children.iter()
+                .max_by(|&&a, &&b| {
+                    let uct_a = self.uct(&nodes[a], log_visits);
+                    let uct_b = self.uct(&nodes[b], log_visits);
+                    uct_a.partial_cmp(&uct_b).expect("UCT values are never NaN")
+                })
+                .expect("The node has children");
+        }
+        // Expansion
+        if !nodes[index].untried.is_empty() {
+            let pick = rng.gen_range(0, nodes[index].untried.len());
+            let coord = nodes[index].untried.swap_remove(pick);
+            let mut turn = nodes[index].turn;
+            turn.make_move(coord).expect("Untried moves are legal");
+            let mover = nodes[index].turn.get_state();
+            let child = nodes.len();
+            nodes.push(Node::new(turn, Some(coord), mover, Some(index)));
+            nodes[index].children.push(child);
+            index = child;
+        }
+        // Playout
+        let final_turn = self.play_out(nodes[index].turn, rng);
+        let (dark, light) = final_turn.get_score();
+        // Backpropagation
+        let mut next = Some(index);
+        while let Some(index) = next {
+            let node = &mut nodes[index];
+            node.visits += 1;
+            node.wins += match node.mover {
+                Some(::Side::Dark) if dark > light => 1.0,
+                Some(::Side::Light) if light > dark => 1.0,
+                _ if dark == light => 0.5,
+                _ => 0.0,
+            };
+            next = node.parent;
+        }
+    }
+
+    /// The UCT value of a node, given the logarithm of its parent's visits.
+    #[inline(always)]
+    fn uct(&self, node: &Node, log_parent_visits: f64) -> f64 {
+        let visits = node.visits as f64;
+        node.wins / visits + self.exploration * (log_parent_visits / visits).sqrt()
+    }
+
+    /// Plays the game out from the given turn, returning the final turn.
+    fn play_out<R: Rng>(&self, mut turn: Turn, rng: &mut R) -> Turn {
+        while !turn.is_end_state() {
+            let moves = turn.get_legal_moves();
+            let coord = match self.playout {
+                Playout::Random => *rng.choose(&moves).expect("A running turn has legal moves"),
+                Playout::Heuristic => {
+                    if rng.gen::<f64>() < 0.8 {
+                        let best = moves.iter().map(|&coord| square_value(coord)).max().expect("A running turn has legal moves");
+                        let best_moves: Vec<Coord> = moves.into_iter().filter(|&coord| square_value(coord) == best).collect();
+                        *rng.choose(&best_moves).expect("There is at least a best move")
+                    } else {
+                        *rng.choose(&moves).expect("A running turn has legal moves")
+                    }
+                }
+            };
+            turn.make_move(coord).expect("Legal moves can be played");
+        }
+        turn
+    }
+}
+
+impl<A> IsPlayer<A> for MctsPlayer {
+    /// Searches from scratch, since the tree cannot be stored by a shared reference.
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<A>> {
+        let tree = self.run(Tree::new(*turn), &mut rand::thread_rng());
+        most_visited(tree.root_moves())
+            .map(|(coord, _)| PlayerAction::Move(coord))
+            .ok_or(::ReversiError::EndedGame(*turn))
+    }
+}
+
+impl<A> IsStatefulPlayer<A> for MctsPlayer {
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>> {
+        self.search(turn)
+            .map(|(coord, _)| PlayerAction::Move(coord))
+            .ok_or(::ReversiError::EndedGame(*turn))
+    }
+
+    fn on_game_start(&mut self, _side: ::Side, _turn: &Turn) {
+        self.tree = None;
+    }
+
+    fn on_undo(&mut self, _turn: &Turn) {
+        self.tree = None;
+    }
+
+    fn on_game_end(&mut self, _result: &GameResult) {
+        self.tree = None;
+    }
+}
@@ -0,0 +1,63 @@
+//! Implementation of pondering: searching in the background while the opponent thinks.
+
+use std::panic;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use turn::*;
+
+/// A search running in a background thread on the turn a player predicts will follow the opponent's move.
+/// If the prediction turns out to be right, the search's result can be reused.
+/// Dropping it tells the search to stop, without waiting for it.
+pub struct Ponder<T> {
+    predicted_turn: Turn,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<T>>,
+}
+
+impl<T: Send + 'static> Ponder<T> {
+    /// Starts searching the predicted turn in a background thread.
+    /// The search is given a flag which is raised once the actual turn is known, whether its result is needed or not,
+    /// and which it has to check periodically to return as soon as possible.
+    pub fn start<F>(predicted_turn: Turn, search: F) -> Ponder<T>
+        where F: FnOnce(Turn, &AtomicBool) -> T + Send + 'static
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        Ponder {
+            predicted_turn,
+            stop,
+            handle: Some(thread::spawn(move || search(predicted_turn, &flag))),
+        }
+    }
+
+    /// Returns the turn the search is running on.
+    #[inline(always)]
+    pub fn get_predicted_turn(&self) -> &Turn {
+        &self.predicted_turn
+    }
+
+    /// Ends pondering once the actual turn is known: the search is told to stop.
+    /// If the actual turn matches the predicted one, it waits for the search to return and returns its result
+    /// (if the search panicked, the panic is propagated). Otherwise, `None` is returned.
+    pub fn finish(mut self, actual_turn: &Turn) -> Option<T> {
+        self.stop.store(true, Ordering::Relaxed);
+        if self.predicted_turn != *actual_turn {
+            return None;
+        }
+        let handle = self.handle.take().expect("The search is only joined once");
+        match handle.join() {
+            Ok(result) => Some(result),
+            Err(err) => panic::resume_unwind(err),
+        }
+    }
+
+    /// Tells the search to stop, discarding its result, as dropping does.
+    pub fn stop(self) {}
+}
+
+impl<T> Drop for Ponder<T> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,361 @@
+//! Implementation of Elo and Glicko-2 ratings, computed from game results and stored in a local file.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::f64;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use board::NUM_CELLS;
+use game::*;
+use ggf;
+use arena::expected_score;
+use tournament::Tournament;
+
+/// The scale factor between Glicko and Glicko-2 ratings.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The convergence tolerance of the volatility update.
+const VOLATILITY_TOLERANCE: f64 = 0.000_001;
+
+/// How a game's result is turned into a score between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreMode {
+    /// 1 for a win, 0.5 for a draw and 0 for a loss.
+    Result,
+    /// The fraction of the final disks which are the player's, for games played to the end.
+    /// Other games are scored by their result.
+    Disks,
+}
+
+/// A game between two named players, as used for rating them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatedGame {
+    pub dark: String,
+    pub light: String,
+    pub result: GameResult,
+}
+
+impl RatedGame {
+    /// Creates a new game between the given players with the given result.
+    pub fn new(dark: &str, light: &str, result: GameResult) -> RatedGame {
+        RatedGame {
+            dark: dark.to_string(),
+            light: light.to_string(),
+            result,
+        }
+    }
+
+    /// Returns the score of Dark in the game.
+    pub fn get_dark_score(&self, mode: ScoreMode) -> f64 {
+        match (mode, self.result) {
+            (ScoreMode::Disks, GameResult::WinByDisks(_, (dark, light))) => dark as f64 / (dark as f64 + light as f64),
+            (_, result) => match result.get_winner() {
+                Some(::Side::Dark) => 1.0,
+                Some(::Side::Light) => 0.0,
+                None => 0.5,
+            },
+        }
+    }
+}
+
+/// Returns the games played so far in a tournament.
+pub fn tournament_games(tournament: &Tournament) -> Vec<RatedGame> {
+    let names = tournament.get_names();
+    tournament.get_results().into_iter()
+        .map(|(pairing, result, _)| RatedGame::new(&names[pairing.dark], &names[pairing.light], result))
+        .collect()
+}
+
+/// Reads games, one per line with tab-separated Dark player, Light player and final disk count (e.g. `40-24`).
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_games<R: BufRead>(reader: R) -> io::Result<Vec<RatedGame>> {
+    let mut games = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid game at line {}", number + 1));
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        let mut disks = fields[2].trim().splitn(2, '-').map(|count| count.parse::<u8>());
+        let (dark, light) = match (disks.next(), disks.next()) {
+            (Some(Ok(dark)), Some(Ok(light))) => (dark, light),
+            _ => return Err(invalid()),
+        };
+        let result = match dark.cmp(&light) {
+            Ordering::Greater => GameResult::WinByDisks(::Side::Dark, (dark, light)),
+            Ordering::Less => GameResult::WinByDisks(::Side::Light, (dark, light)),
+            Ordering::Equal => GameResult::Draw,
+        };
+        games.push(RatedGame::new(fields[0].trim(), fields[1].trim(), result));
+    }
+    Ok(games)
+}
+
+/// Reads games in GGF, as saved by NBoard and game servers, with the players named by their `PB` and `PW` tags.
+/// Results are taken from the `RE` tag (Dark's disk margin, followed by `:r` for a resignation or `:t` for a loss
+/// on time), or else from the final position of games played to the end.
+pub fn read_ggf_games(text: &str) -> io::Result<Vec<RatedGame>> {
+    let mut games = Vec::new();
+    for (number, game) in text.split("(;").skip(1).enumerate() {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid game number {}", number + 1));
+        let tag = |name| ggf::get_tag(game, name).map_err(|_| invalid());
+        let (dark, light) = match (tag("PB")?, tag("PW")?) {
+            (Some(dark), Some(light)) => (dark, light),
+            _ => return Err(invalid()),
+        };
+        let result = match tag("RE")? {
+            Some(result) => parse_ggf_result(result).ok_or_else(invalid)?,
+            None => {
+                let turn = ggf::parse_game(game).and_then(|game| game.get_current_turn()).map_err(|_| invalid())?;
+                GameResult::from_final_turn(&turn).ok_or_else(invalid)?
+            }
+        };
+        games.push(RatedGame::new(dark.trim(), light.trim(), result));
+    }
+    Ok(games)
+}
+
+/// Parses the value of a GGF `RE` tag, or returns `None` if it is invalid.
+fn parse_ggf_result(result: &str) -> Option<GameResult> {
+    let mut parts = result.trim().splitn(2, ':');
+    let margin = parts.next()?.parse::<f64>().ok().filter(|margin| margin.abs() <= NUM_CELLS as f64)?;
+    let winner = match margin.partial_cmp(&0.0)? {
+        Ordering::Greater => Some(::Side::Dark),
+        Ordering::Less => Some(::Side::Light),
+        Ordering::Equal => None,
+    };
+    match (winner, parts.next()) {
+        (Some(winner), Some("r")) => Some(GameResult::WinByResignation(winner)),
+        (Some(winner), Some("t")) => Some(GameResult::WinByTimeout(winner)),
+        (Some(winner), None) => {
+            let dark = ((NUM_CELLS as f64 + margin) / 2.0).round() as u8;
+            Some(GameResult::WinByDisks(winner, (dark, NUM_CELLS as u8 - dark)))
+        }
+        (None, None) => Some(GameResult::Draw),
+        _ => None,
+    }
+}
+
+/// The ratings of a player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerRating {
+    /// The Elo rating.
+    pub elo: f64,
+    /// The Glicko-2 rating, on the Glicko scale.
+    pub glicko: f64,
+    /// The Glicko-2 rating deviation, on the Glicko scale.
+    pub deviation: f64,
+    /// The Glicko-2 rating volatility.
+    pub volatility: f64,
+}
+
+impl Default for PlayerRating {
+    /// Unrated players start from 1500 Elo, and from Glicko-2 rating 1500 with deviation 350 and volatility 0.06.
+    fn default() -> PlayerRating {
+        PlayerRating {
+            elo: 1500.0,
+            glicko: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+/// The ratings of a pool of players.
+#[derive(Debug, Clone)]
+pub struct Ratings {
+    players: BTreeMap<String, PlayerRating>,
+    k_factor: f64,
+    tau: f64,
+}
+
+impl Ratings {
+    /// Creates a new empty pool, with Elo K-factor 32 and Glicko-2 system constant 0.5.
+    pub fn new() -> Ratings {
+        Ratings {
+            players: BTreeMap::new(),
+            k_factor: 32.0,
+            tau: 0.5,
+        }
+    }
+
+    /// Sets the Elo K-factor, that is, the largest change of rating a single game can make.
+    #[inline(always)]
+    pub fn set_k_factor(&mut self, k_factor: f64) {
+        self.k_factor = k_factor;
+    }
+
+    /// Sets the Glicko-2 system constant, constraining the change in volatility over time.
+    #[inline(always)]
+    pub fn set_tau(&mut self, tau: f64) {
+        self.tau = tau;
+    }
+
+    /// Returns the ratings of the given player, if rated.
+    #[inline(always)]
+    pub fn get(&self, name: &str) -> Option<&PlayerRating> {
+        self.players.get(name)
+    }
+
+    /// Sets the ratings of the given player.
+    #[inline(always)]
+    pub fn set(&mut self, name: &str, rating: PlayerRating) {
+        self.players.insert(name.to_string(), rating);
+    }
+
+    /// Returns all the players with their ratings, sorted by name.
+    pub fn get_players(&self) -> Vec<(&str, &PlayerRating)> {
+        self.players.iter().map(|(name, rating)| (name.as_str(), rating)).collect()
+    }
+
+    /// Updates the Elo ratings with the given games, one after the other. Unrated players are added to the pool.
+    pub fn update_elo(&mut self, games: &[RatedGame], mode: ScoreMode) {
+        for game in games {
+            let dark = self.players.get(&game.dark).cloned().unwrap_or_default().elo;
+            let light = self.players.get(&game.light).cloned().unwrap_or_default().elo;
+            let change = self.k_factor * (game.get_dark_score(mode) - expected_score(dark - light));
+            self.players.entry(game.dark.clone()).or_default().elo += change;
+            self.players.entry(game.light.clone()).or_default().elo -= change;
+        }
+    }
+
+    /// Updates the Glicko-2 ratings with the given games, all played in the same rating period.
+    /// The deviation of rated players who did not play grows with their volatility. Unrated players are added to the pool.
+    pub fn update_glicko(&mut self, games: &[RatedGame], mode: ScoreMode) {
+        for game in games {
+            self.players.entry(game.dark.clone()).or_default();
+            self.players.entry(game.light.clone()).or_default();
+        }
+        let old = self.players.clone();
+        for (name, rating) in &mut self.players {
+            // Each game as (opponent's rating, opponent's deviation, score), on the Glicko-2 scale.
+            let results: Vec<(f64, f64, f64)> = games.iter()
+                .filter_map(|game| {
+                    let (opponent, score) = if game.dark == *name {
+                        (&game.light, game.get_dark_score(mode))
+                    } else if game.light == *name {
+                        (&game.dark, 1.0 - game.get_dark_score(mode))
+                    } else {
+                        return None;
+                    };
+                    let opponent = &old[opponent];
+                    Some(((opponent.glicko - 1500.0) / GLICKO2_SCALE, opponent.deviation / GLICKO2_SCALE, score))
+                })
+                .collect();
+            let phi = rating.deviation / GLICKO2_SCALE;
+            if results.is_empty() {
+                rating.deviation = (phi * phi + rating.volatility * rating.volatility).sqrt() * GLICKO2_SCALE;
+                continue;
+            }
+            let mu = (rating.glicko - 1500.0) / GLICKO2_SCALE;
+            let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (f64::consts::PI * f64::consts::PI)).sqrt();
+            let expected = |mu_j: f64, phi_j: f64| 1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp());
+            let variance = 1.0 / results.iter()
+                .map(|&(mu_j, phi_j, _)| g(phi_j).powi(2) * expected(mu_j, phi_j) * (1.0 - expected(mu_j, phi_j)))
+                .sum::<f64>();
+            let improvement: f64 = results.iter().map(|&(mu_j, phi_j, score)| g(phi_j) * (score - expected(mu_j, phi_j))).sum();
+            let delta = variance * improvement;
+            let volatility = new_volatility(phi, rating.volatility, variance, delta, self.tau);
+            let phi_star = (phi * phi + volatility * volatility).sqrt();
+            let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / variance).sqrt();
+            rating.glicko = (mu + new_phi * new_phi * improvement) * GLICKO2_SCALE + 1500.0;
+            rating.deviation = new_phi * GLICKO2_SCALE;
+            rating.volatility = volatility;
+        }
+    }
+
+    /// Loads ratings from a ratings file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Ratings> {
+        Ratings::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Saves the ratings to a ratings file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads ratings, one player per line with tab-separated name, Elo rating, Glicko-2 rating, deviation,
+    /// and volatility. Empty lines and lines starting with `#` are ignored.
+    /// Every value has to be finite, and deviations and volatilities positive.
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Ratings> {
+        let mut ratings = Ratings::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid rating at line {}", number + 1));
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 5 {
+                return Err(invalid());
+            }
+            let number = |index: usize| {
+                fields[index].trim().parse::<f64>().ok().filter(|value| value.is_finite()).ok_or_else(invalid)
+            };
+            let positive = |index: usize| number(index).and_then(|value| if value > 0.0 { Ok(value) } else { Err(invalid()) });
+            ratings.set(fields[0], PlayerRating {
+                elo: number(1)?,
+                glicko: number(2)?,
+                deviation: positive(3)?,
+                volatility: positive(4)?,
+            });
+        }
+        Ok(ratings)
+    }
+
+    /// Writes the ratings in the format of ratings files (see `read_from`).
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "# name\telo\tglicko\tdeviation\tvolatility")?;
+        for (name, rating) in &self.players {
+            writeln!(writer, "{}\t{}\t{}\t{}\t{}", name, rating.elo, rating.glicko, rating.deviation, rating.volatility)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Ratings {
+    fn default() -> Ratings {
+        Ratings::new()
+    }
+}
+
+/// Computes the new volatility by the Illinois algorithm, as in Glickman's description of Glicko-2.
+fn new_volatility(phi: f64, sigma: f64, variance: f64, delta: f64, tau: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let denominator = phi * phi + variance + x.exp();
+        x.exp() * (delta * delta - denominator + variance) / (2.0 * denominator * denominator) - (x - a) / (tau * tau)
+    };
+    let mut lower = a;
+    let mut upper = if delta * delta > phi * phi + variance {
+        (delta * delta - phi * phi - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * tau) < 0.0 {
+            k += 1.0;
+        }
+        a - k * tau
+    };
+    let mut f_lower = f(lower);
+    let mut f_upper = f(upper);
+    while (upper - lower).abs() > VOLATILITY_TOLERANCE {
+        let next = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+        let f_next = f(next);
+        if f_next * f_upper <= 0.0 {
+            lower = upper;
+            f_lower = f_upper;
+        } else {
+            f_lower /= 2.0;
+        }
+        upper = next;
+        f_upper = f_next;
+    }
+    (lower / 2.0).exp()
+}
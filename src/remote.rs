@@ -0,0 +1,218 @@
+//! Implementation of remote players, playing from another process (possibly on another machine) over TCP.
+//!
+//! The game's host drives a `RemotePlayer`, which proxies everything to the process running the actual player
+//! with `serve_player`. They talk with a line protocol, where turns are written as by `Turn`'s `Display`:
+//!
+//! - `start SIDE TURN`: the game starts, and the player plays the given side (`dark` or `light`);
+//! - `move TURN [MILLISECONDS]`: the player has to move, with the given time left if the game is timed,
+//!   and answers `play COORD` (e.g. `play f5`) or `resign`;
+//! - `opponent COORD TURN`: the opponent has moved, reaching the given turn;
+//! - `undo TURN`: moves have been undone, back to the given turn;
+//! - `end RESULT`: the game is over, where the result is `disks SIDE DARK LIGHT`, `timeout SIDE`,
+//!   `resignation SIDE`, `forfeit SIDE` (with the winning side and, for `disks`, the final disk count),
+//!   `draw` or `agreement`.
+//!
+//! A remote player which disconnects, does not answer in time or breaks the protocol is unavailable,
+//! and loses the game by forfeit (see `ReversiError::PlayerUnavailable`).
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+use board::*;
+use turn::*;
+use game::*;
+use ::Result;
+
+/// The shortest timeout used when waiting for a remote player, as sockets do not accept a zero timeout.
+const MIN_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// The connection to a remote player.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+/// A player proxying the game to a remote process, which runs the actual player with `serve_player`.
+/// The remote player has to answer each request within the player's timeout (and within its time left,
+/// if the game is timed).
+pub struct RemotePlayer {
+    connection: Mutex<Connection>,
+    timeout: Duration,
+}
+
+impl RemotePlayer {
+    /// Creates a new remote player on an open connection, with the given timeout for answering requests.
+    pub fn new(stream: TcpStream, timeout: Duration) -> io::Result<RemotePlayer> {
+        stream.set_nodelay(true)?;
+        Ok(RemotePlayer {
+            connection: Mutex::new(Connection {
+                reader: BufReader::new(stream.try_clone()?),
+                writer: stream,
+            }),
+            timeout,
+        })
+    }
+
+    /// Connects to a remote player at the given address, with the given timeout for answering requests.
+    pub fn connect<S: ToSocketAddrs>(address: S, timeout: Duration) -> io::Result<RemotePlayer> {
+        RemotePlayer::new(TcpStream::connect(address)?, timeout)
+    }
+
+    /// Sends a notification. Failures are ignored: they will make the player unavailable when asked to move.
+    fn notify(&self, line: &str) {
+        if let Ok(mut connection) = self.connection.lock() {
+            let _ = writeln!(connection.writer, "{}", line);
+        }
+    }
+
+    /// Asks the remote player to move on the given turn and waits for its answer.
+    fn request<A>(&self, turn: &Turn, time_left: Option<Duration>) -> Result<PlayerAction<A>> {
+        let side = turn.get_state().ok_or(::ReversiError::EndedGame(*turn))?;
+        let unavailable = |_| ::ReversiError::PlayerUnavailable(side);
+        let mut connection = self.connection.lock().map_err(|_| ::ReversiError::PlayerUnavailable(side))?;
+        let timeout = time_left.map_or(self.timeout, |time_left| time_left.min(self.timeout)).max(MIN_TIMEOUT);
+        connection.writer.set_read_timeout(Some(timeout)).map_err(unavailable)?;
+        match time_left {
+            Some(time_left) => writeln!(connection.writer, "move {} {}", turn, time_left.as_millis()),
+            None => writeln!(connection.writer, "move {}", turn),
+        }.map_err(unavailable)?;
+        let mut line = String::new();
+        if connection.reader.read_line(&mut line).map_err(unavailable)? == 0 {
+            return Err(::ReversiError::PlayerUnavailable(side));
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match *tokens.as_slice() {
+            ["play", coord] => coord.parse().map(PlayerAction::Move).map_err(|_| ::ReversiError::PlayerUnavailable(side)),
+            ["resign"] => Ok(PlayerAction::Resign),
+            _ => Err(::ReversiError::PlayerUnavailable(side)),
+        }
+    }
+}
+
+impl<A> IsPlayer<A> for RemotePlayer {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<A>> {
+        self.request(turn, None)
+    }
+
+    fn make_timed_move(&self, turn: &Turn, time_left: Duration) -> Result<PlayerAction<A>> {
+        self.request(turn, Some(time_left))
+    }
+}
+
+/// Remote players are notified about the progress of the game, which they forward to the remote process.
+impl<A> IsStatefulPlayer<A> for RemotePlayer {
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<A>> {
+        self.request(turn, None)
+    }
+
+    fn make_timed_move(&mut self, turn: &Turn, time_left: Duration) -> Result<PlayerAction<A>> {
+        self.request(turn, Some(time_left))
+    }
+
+    fn on_game_start(&mut self, side: ::Side, turn: &Turn) {
+        self.notify(&format!("start {} {}", side_name(side), turn));
+    }
+
+    fn on_opponent_move(&mut self, coord: Coord, turn: &Turn) {
+        self.notify(&format!("opponent {} {}", coord, turn));
+    }
+
+    fn on_undo(&mut self, turn: &Turn) {
+        self.notify(&format!("undo {}", turn));
+    }
+
+    fn on_game_end(&mut self, result: &GameResult) {
+        self.notify(&format!("end {}", format_result(result)));
+    }
+}
+
+/// Runs the given player for a remote host, answering its requests on the given connection,
+/// until the game is over or the host disconnects.
+/// Actions other than moves and resignation, and errors of the player, are sent as resignation.
+pub fn serve_player<A, P: IsStatefulPlayer<A>>(mut player: P, stream: TcpStream) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid request: {}", line));
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let parse_turn = |tokens: &[&str]| tokens.join(" ").parse::<Turn>().map_err(|_| invalid());
+        match *tokens.as_slice() {
+            ["start", side, ref turn @ ..] => {
+                let side = match side {
+                    "dark" => ::Side::Dark,
+                    "light" => ::Side::Light,
+                    _ => return Err(invalid()),
+                };
+                player.on_game_start(side, &parse_turn(turn)?);
+            }
+            ["move", cells, side] => {
+                let action = player.make_move(&parse_turn(&[cells, side])?);
+                answer(&mut writer, action)?;
+            }
+            ["move", cells, side, time_left] => {
+                let time_left = Duration::from_millis(time_left.parse().map_err(|_| invalid())?);
+                let action = player.make_timed_move(&parse_turn(&[cells, side])?, time_left);
+                answer(&mut writer, action)?;
+            }
+            ["opponent", coord, ref turn @ ..] => {
+                player.on_opponent_move(coord.parse().map_err(|_| invalid())?, &parse_turn(turn)?);
+            }
+            ["undo", ref turn @ ..] => player.on_undo(&parse_turn(turn)?),
+            ["end", ref result @ ..] => {
+                player.on_game_end(&parse_result(result).ok_or_else(invalid)?);
+                return Ok(());
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(())
+}
+
+/// Sends the player's answer to a move request.
+fn answer<A, W: Write>(writer: &mut W, action: Result<PlayerAction<A>>) -> io::Result<()> {
+    match action {
+        Ok(PlayerAction::Move(coord)) => writeln!(writer, "play {}", coord),
+        _ => writeln!(writer, "resign"),
+    }
+}
+
+/// Returns the name of a side as used by the protocol.
+fn side_name(side: ::Side) -> &'static str {
+    match side {
+        ::Side::Dark => "dark",
+        ::Side::Light => "light",
+    }
+}
+
+/// Writes a result as in the protocol's `end` message.
+fn format_result(result: &GameResult) -> String {
+    match *result {
+        GameResult::WinByDisks(side, (dark, light)) => format!("disks {} {} {}", side_name(side), dark, light),
+        GameResult::Draw => "draw".to_string(),
+        GameResult::WinByTimeout(side) => format!("timeout {}", side_name(side)),
+        GameResult::WinByResignation(side) => format!("resignation {}", side_name(side)),
+        GameResult::WinByForfeit(side) => format!("forfeit {}", side_name(side)),
+        GameResult::DrawByAgreement => "agreement".to_string(),
+    }
+}
+
+/// Parses a result written as in the protocol's `end` message.
+fn parse_result(tokens: &[&str]) -> Option<GameResult> {
+    let side = |name: &str| match name {
+        "dark" => Some(::Side::Dark),
+        "light" => Some(::Side::Light),
+        _ => None,
+    };
+    match *tokens {
+        ["disks", winner, dark, light] => Some(GameResult::WinByDisks(side(winner)?, (dark.parse().ok()?, light.parse().ok()?))),
+        ["draw"] => Some(GameResult::Draw),
+        ["timeout", winner] => Some(GameResult::WinByTimeout(side(winner)?)),
+        ["resignation", winner] => Some(GameResult::WinByResignation(side(winner)?)),
+        ["forfeit", winner] => Some(GameResult::WinByForfeit(side(winner)?)),
+        ["agreement"] => Some(GameResult::DrawByAgreement),
+        _ => None,
+    }
+}
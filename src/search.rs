@@ -0,0 +1,117 @@
+//! Implementation of an alpha-beta search player, scoring the leaves with an evaluator.
+
+use std::f32;
+use board::*;
+use turn::*;
+use game::*;
+use eval::*;
+use ::Result;
+
+/// A player searching the game tree up to a fixed depth with alpha-beta pruning.
+/// Ended turns are scored by their actual score difference, the other leaves by the evaluator.
+#[derive(Debug, Clone)]
+pub struct SearchPlayer<E> {
+    evaluator: E,
+    depth: u8,
+}
+
+impl<E: IsEvaluator> SearchPlayer<E> {
+    /// Creates a new player searching with the given evaluator up to the given depth (at least one move).
+    pub fn new(evaluator: E, depth: u8) -> SearchPlayer<E> {
+        SearchPlayer {
+            evaluator,
+            depth: depth.max(1),
+        }
+    }
+
+    /// Returns the player's evaluator.
+    #[inline(always)]
+    pub fn get_evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    /// Returns the player's search depth.
+    #[inline(always)]
+    pub fn get_depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Searches the given turn, returning the best move together with its score
+    /// (positive when Light is winning, as in `Turn::get_score_diff`).
+    pub fn search(&self, turn: &Turn) -> Option<(Coord, f32)> {
+        let side = turn.get_state()?;
+        let mut alpha = f32::NEG_INFINITY;
+        let mut beta = f32::INFINITY;
+        let mut best = None;
+        for coord in turn.get_legal_moves() {
+            let mut next_turn = *turn;
+            next_turn.make_move(coord).expect("The move is legal");
+            let score = self.alpha_beta(&next_turn, self.depth - 1, alpha, beta);
+            match side {
+                ::Side::Light if best.is_none() || score > alpha => {
+                    alpha = score;
+                    best = Some((coord, score));
+                }
+                ::Side::Dark if best.is_none() || score < beta => {
+                    beta = score;
+                    best = Some((coord, score));
+                }
+                _ => {}
+            }
+        }
+        best
+    }
+
+    /// Scores every legal move of the given turn by a full-window search
+    /// (positive when Light is winning, as in `Turn::get_score_diff`).
+    pub fn score_moves(&self, turn: &Turn) -> Vec<(Coord, f32)> {
+        turn.get_legal_moves().into_iter()
+            .map(|coord| {
+                let mut next_turn = *turn;
+                next_turn.make_move(coord).expect("The move is legal");
+                (coord, self.alpha_beta(&next_turn, self.depth - 1, f32::NEG_INFINITY, f32::INFINITY))
+            })
+            .collect()
+    }
+
+    /// Scores a turn by searching it to the given depth, within the `(alpha, beta)` window.
+    fn alpha_beta(&self, turn: &Turn, depth: u8, mut alpha: f32, mut beta: f32) -> f32 {
+        match turn.get_state() {
+            None => turn.get_score_diff() as f32,
+            Some(_) if depth == 0 => self.evaluator.evaluate(turn),
+            Some(side) => {
+                let mut best = match side {
+                    ::Side::Light => f32::NEG_INFINITY,
+                    ::Side::Dark => f32::INFINITY,
+                };
+                for coord in turn.get_legal_moves() {
+                    let mut next_turn = *turn;
+                    next_turn.make_move(coord).expect("The move is legal");
+                    let score = self.alpha_beta(&next_turn, depth - 1, alpha, beta);
+                    match side {
+                        ::Side::Light => {
+                            best = best.max(score);
+                            alpha = alpha.max(score);
+                        }
+                        ::Side::Dark => {
+                            best = best.min(score);
+                            beta = beta.min(score);
+                        }
+                    }
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+                best
+            }
+        }
+    }
+}
+
+impl<A, E: IsEvaluator> IsPlayer<A> for SearchPlayer<E> {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<A>> {
+        self.search(turn)
+            .map(|(coord, _)| PlayerAction::Move(coord))
+            .ok_or(::ReversiError::EndedGame(*turn))
+    }
+}
@@ -0,0 +1,255 @@
+//! Implementation of self-play games between engines, recording every position to generate datasets.
+
+use std::f64;
+use std::io::{self, Read, Write};
+use rand::{self, Rng, SeedableRng, XorShiftRng};
+use board::*;
+use turn::*;
+pub use engine::{Engine, ScoreUnit};
+
+/// The magic bytes opening a binary records file.
+const RECORDS_MAGIC: &[u8; 4] = b"RVSP";
+
+/// The version of the binary records file format.
+const RECORDS_VERSION: u32 = 2;
+
+/// A position reached in a self-play game, together with the move played from it.
+#[derive(Debug, Clone, Copy)]
+pub struct Record {
+    /// The position, including the side to move.
+    pub turn: Turn,
+    /// The move played.
+    pub coord: Coord,
+    /// The engine's score of the move, from Light's viewpoint (see `Engine::choose_move`).
+    pub score: f32,
+    /// What the score measures, which depends on the engine.
+    pub unit: ScoreUnit,
+    /// The final score difference of the game (positive when Light won, as in `Turn::get_score_diff`).
+    pub result: i16,
+}
+
+/// Plays self-play games between two engines, recording every position.
+/// Games can be made varied by choosing the first moves with some temperature (see `Engine::choose_move`).
+pub struct SelfPlay {
+    dark: Engine,
+    light: Engine,
+    noise_plies: usize,
+    temperature: f64,
+    rng: XorShiftRng,
+}
+
+impl SelfPlay {
+    /// Creates a new generator with the given engines, playing every move at zero temperature.
+    pub fn new(dark: Engine, light: Engine) -> SelfPlay {
+        SelfPlay {
+            dark,
+            light,
+            noise_plies: 0,
+            temperature: 1.0,
+            rng: rand::weak_rng(),
+        }
+    }
+
+    /// Sets how many moves from the start of each game are chosen with temperature.
+    #[inline(always)]
+    pub fn set_noise_plies(&mut self, noise_plies: usize) {
+        self.noise_plies = noise_plies;
+    }
+
+    /// Sets the temperature of the first moves of each game.
+    #[inline(always)]
+    pub fn set_temperature(&mut self, temperature: f64) {
+        self.temperature = temperature;
+    }
+
+    /// Seeds the generator and its engines, to make the games reproducible.
+    pub fn set_seed(&mut self, seed: [u32; 4]) {
+        self.rng = XorShiftRng::from_seed(seed);
+        let dark_seed = self.rng.gen();
+        let light_seed = self.rng.gen();
+        self.dark.set_seed(dark_seed);
+        self.light.set_seed(light_seed);
+    }
+
+    /// Plays a game, returning the records of all its positions.
+    pub fn play_game(&mut self) -> Vec<Record> {
+        self.dark.reset();
+        self.light.reset();
+        let mut records = Vec::new();
+        let mut turn = Turn::first_turn();
+        while let Some(side) = turn.get_state() {
+            let temperature = if records.len() < self.noise_plies { self.temperature } else { 0.0 };
+            let engine = match side {
+                ::Side::Dark => &mut self.dark,
+                ::Side::Light => &mut self.light,
+            };
+            let (coord, score) = engine.choose_move(&turn, temperature, &mut self.rng).expect("A running turn has legal moves");
+            records.push(Record { turn, coord, score, unit: engine.get_score_unit(), result: 0 });
+            turn.make_move(coord).expect("Engines play legal moves");
+        }
+        for record in &mut records {
+            record.result = turn.get_score_diff();
+        }
+        records
+    }
+}
+
+/// The formats records can be written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+    /// Compact binary format (see `read_records`).
+    Binary,
+    /// Comma-separated values, with a header line and columns `board,side,move,score,unit,result`,
+    /// where the board and the side are written as displayed by `Turn`, the move in algebraic notation
+    /// and the unit as `none`, `disks` or `probability`.
+    Csv,
+}
+
+/// Writes records one game after the other in the given format.
+pub struct RecordWriter<W: Write> {
+    writer: W,
+    format: RecordFormat,
+}
+
+impl<W: Write> RecordWriter<W> {
+    /// Creates a new writer, writing the file's header.
+    pub fn new(mut writer: W, format: RecordFormat) -> io::Result<RecordWriter<W>> {
+        match format {
+            RecordFormat::Binary => {
+                writer.write_all(RECORDS_MAGIC)?;
+                writer.write_all(&RECORDS_VERSION.to_le_bytes())?;
+                writer.write_all(&(BOARD_SIZE as u32).to_le_bytes())?;
+            }
+            RecordFormat::Csv => writeln!(writer, "board,side,move,score,unit,result")?,
+        }
+        Ok(RecordWriter { writer, format })
+    }
+
+    /// Writes the given records.
+    pub fn write(&mut self, records: &[Record]) -> io::Result<()> {
+        for record in records {
+            match self.format {
+                RecordFormat::Binary => {
+                    let mut bytes = [0u8; NUM_CELLS + 1];
+                    for (index, cell) in record.turn.get_board().get_board().iter().flat_map(|row| row.iter()).enumerate() {
+                        bytes[index] = side_byte(cell.map(|disk| disk.get_side()));
+                    }
+                    bytes[NUM_CELLS] = side_byte(record.turn.get_state());
+                    self.writer.write_all(&bytes)?;
+                    self.writer.write_all(&[record.coord.get_row() as u8, record.coord.get_col() as u8])?;
+                    self.writer.write_all(&record.score.to_le_bytes())?;
+                    self.writer.write_all(&[unit_byte(record.unit)])?;
+                    self.writer.write_all(&record.result.to_le_bytes())?;
+                }
+                RecordFormat::Csv => {
+                    let notation = record.turn.to_string();
+                    let (board, side) = notation.split_at(NUM_CELLS);
+                    let unit = match record.unit {
+                        ScoreUnit::None => "none",
+                        ScoreUnit::Disks => "disks",
+                        ScoreUnit::Probability => "probability",
+                    };
+                    writeln!(self.writer, "{},{},{},{},{},{}", board, side.trim(), record.coord, record.score, unit, record.result)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the writer and gives it back.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads records in the binary format: magic bytes, then version and board size as little-endian `u32`s,
+/// then the records until the end of the file. Each record is given by one byte per cell and one for the side
+/// to move (0 for none, 1 for Dark, 2 for Light), row and column bytes of the move,
+/// the score as a little-endian `f32`, its unit as a byte (0 for none, 1 for disks, 2 for probability)
+/// and the result as a little-endian `i16`.
+pub fn read_records<R: Read>(reader: &mut R) -> io::Result<Vec<Record>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut header = [0u8; 12];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != RECORDS_MAGIC {
+        return Err(invalid("Not a records file"));
+    }
+    if header[4..8] != RECORDS_VERSION.to_le_bytes() {
+        return Err(invalid("Unsupported records file version"));
+    }
+    if header[8..] != (BOARD_SIZE as u32).to_le_bytes() {
+        return Err(invalid("Records are for a different board size"));
+    }
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let size = NUM_CELLS + 1 + 2 + 4 + 1 + 2;
+    if bytes.len() % size != 0 {
+        return Err(invalid("Truncated record"));
+    }
+    bytes.chunks(size).map(|chunk| {
+        let mut cells = [[None; BOARD_SIZE]; BOARD_SIZE];
+        for (index, &byte) in chunk[..NUM_CELLS].iter().enumerate() {
+            cells[index / BOARD_SIZE][index % BOARD_SIZE] = byte_side(byte).ok_or_else(|| invalid("Invalid cell"))?.map(Disk::new);
+        }
+        let side = byte_side(chunk[NUM_CELLS]).and_then(|side| side).ok_or_else(|| invalid("Invalid side"))?;
+        let (row, col) = (chunk[NUM_CELLS + 1] as usize, chunk[NUM_CELLS + 2] as usize);
+        if row >= BOARD_SIZE || col >= BOARD_SIZE {
+            return Err(invalid("Move out of the board"));
+        }
+        let mut score = [0u8; 4];
+        score.copy_from_slice(&chunk[NUM_CELLS + 3..NUM_CELLS + 7]);
+        let unit = byte_unit(chunk[NUM_CELLS + 7]).ok_or_else(|| invalid("Invalid score unit"))?;
+        let mut result = [0u8; 2];
+        result.copy_from_slice(&chunk[NUM_CELLS + 8..]);
+        Ok(Record {
+            turn: Turn::new(Board::new(cells), side),
+            coord: Coord::new(row, col),
+            score: f32::from_le_bytes(score),
+            unit,
+            result: i16::from_le_bytes(result),
+        })
+    }).collect()
+}
+
+/// Encodes a side (or the lack of one) as a byte.
+#[inline(always)]
+fn side_byte(side: Option<::Side>) -> u8 {
+    match side {
+        None => 0,
+        Some(::Side::Dark) => 1,
+        Some(::Side::Light) => 2,
+    }
+}
+
+/// Decodes a byte encoding a side (or the lack of one), if valid.
+#[inline(always)]
+fn byte_side(byte: u8) -> Option<Option<::Side>> {
+    match byte {
+        0 => Some(None),
+        1 => Some(Some(::Side::Dark)),
+        2 => Some(Some(::Side::Light)),
+        _ => None,
+    }
+}
+
+/// Encodes a score unit as a byte.
+#[inline(always)]
+fn unit_byte(unit: ScoreUnit) -> u8 {
+    match unit {
+        ScoreUnit::None => 0,
+        ScoreUnit::Disks => 1,
+        ScoreUnit::Probability => 2,
+    }
+}
+
+/// Decodes a byte encoding a score unit, if valid.
+#[inline(always)]
+fn byte_unit(byte: u8) -> Option<ScoreUnit> {
+    match byte {
+        0 => Some(ScoreUnit::None),
+        1 => Some(ScoreUnit::Disks),
+        2 => Some(ScoreUnit::Probability),
+        _ => None,
+    }
+}
@@ -0,0 +1,737 @@
+//! Implementation of a local HTTP server exposing games through a JSON API, as used by web frontends.
+//!
+//! The API is made of the following endpoints, where states are JSON objects with the game's `id`, `board`
+//! (rows of `X` for Dark, `O` for Light and `.` for empty cells), `side` to move (`dark`, `light` or `null`),
+//! `legal_moves`, `score`, `moves` and `result` (`dark`, `light`, `draw` or `null`):
+//!
+//! - `POST /games` creates a new game and returns its state;
+//! - `GET /games/ID` returns the state of a game;
+//! - `POST /games/ID/moves` plays the move given as `{"move": "f5"}` and returns the new state;
+//! - `POST /games/ID/undo` undoes moves until the side to move can play again, as `Game::undo` does
+//!   (games lost on time cannot be undone);
+//! - `POST /games/ID/engine-move` has the server's engine play, or the one given as `{"engine": "search:4"}`
+//!   (up to depth 6 for searches and 10000 iterations for MCTS, without weights files);
+//! - `GET /games/ID/transcript` returns the game's transcript (e.g. `f5d6c3`) as plain text;
+//! - `GET /games/ID/events` upgrades to a WebSocket streaming the game's events (see below).
+//!
+//! Games can be timed by creating them with `{"time": SECONDS, "increment": SECONDS}`, of up to a day each:
+//! states then include the `clocks` of both sides as of the last move, in milliseconds, and a side runs out of
+//! time when it moves (or the game is accessed) after its time is over.
+//!
+//! Errors are returned as `{"error": "..."}` with status 400 (bad request), 404 (unknown game or endpoint),
+//! 409 (the move or undo is not possible in the game's current state, or the game changed during an engine's
+//! search), 500 (the engine failed to move) or 503 (the server has too many connections). Bodies have to be flat
+//! JSON objects, whose values are strings, numbers, booleans or `null`.
+//!
+//! Spectators connected to the events endpoint first receive `{"type": "state", "state": STATE}`, then
+//! a JSON message for each event, as it happens:
+//!
+//! - `{"type": "move", "side": SIDE, "move": "f5", "flips": ["e5"], "score": {"dark": 4, "light": 1}}`;
+//! - `{"type": "pass", "side": SIDE}`, when a side has no legal moves after its opponent's;
+//! - `{"type": "undo", "moves": ["d6", "f5"], "side": SIDE}`, with the moves taken back, last one first;
+//! - `{"type": "clock", "dark": MILLISECONDS, "light": MILLISECONDS}`, after each move of a timed game;
+//! - `{"type": "end", "result": RESULT, "reason": REASON, "score": SCORE}`, where the reason is one of
+//!   `disks`, `timeout`, `resignation`, `forfeit` and `agreement`.
+//!
+//! The server closes the WebSocket after the end of the game, and answers pings and closes from spectators.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use rand;
+use board::*;
+use turn::*;
+use game::*;
+use clock::*;
+use engine::Engine;
+use websocket::*;
+use ::Result;
+
+/// The largest request body the server accepts, in bytes.
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+
+/// The most connections served at once, spectators included. Further ones are answered with status 503.
+const MAX_CONNECTIONS: usize = 256;
+
+/// How long to wait before accepting connections again after failing to, e.g. when out of file descriptors.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// How long a spectator has to answer the server closing its connection.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The deepest search an engine given in a request can run.
+const MAX_REQUEST_DEPTH: u8 = 6;
+
+/// The most iterations an MCTS engine given in a request can run.
+const MAX_REQUEST_ITERATIONS: u32 = 10_000;
+
+/// The longest time or increment a game can be created with, in seconds (a day).
+const MAX_REQUEST_TIME: f64 = 86_400.0;
+
+/// A game hosted by the server. Moves are applied directly: the game has no players.
+pub type ServerGame = Game<(), (), ()>;
+
+/// An HTTP request, reduced to what the API needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// The headers, as names and values.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Creates a new request.
+    pub fn new(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Returns the value of the given header, whose name is case-insensitive.
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(header, _)| header.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    /// Reads an HTTP/1.1 request: its request line, its headers and its body, whose length is given by `Content-Length`.
+    pub fn read_from<R: BufRead>(reader: &mut R) -> io::Result<Request> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut tokens = line.split_whitespace();
+        let (method, path) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/") => (method.to_string(), path.to_string()),
+            _ => return Err(invalid("Invalid request line")),
+        };
+        let mut headers = Vec::new();
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return Err(invalid("Unexpected end of headers"));
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().map_err(|_| invalid("Invalid content length"))?;
+                }
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        if length > MAX_BODY_LENGTH {
+            return Err(invalid("Request body too large"));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let body = String::from_utf8(body).map_err(|_| invalid("Request body is not UTF-8"))?;
+        Ok(Request { method, path, headers, body })
+    }
+}
+
+/// An HTTP response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    /// Creates a response with a JSON body.
+    pub fn json(status: u16, body: String) -> Response {
+        Response {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    /// Creates an error response, with the message in a JSON body.
+    pub fn error(status: u16, message: &str) -> Response {
+        Response::json(status, format!("{{\"error\":{}}}", json_string(message)))
+    }
+
+    /// Writes the response as HTTP/1.1, closing the connection after it.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            409 => "Conflict",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        };
+        write!(writer, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+               self.status, reason, self.content_type, self.body.len(), self.body)?;
+        writer.flush()
+    }
+}
+
+/// A message for the connection of a spectator.
+enum SpectatorMessage {
+    /// An event of the game, to send as a text message.
+    Event(String),
+    /// The game is over: the server closes the connection.
+    End,
+    /// The spectator sent a ping, to answer with a pong with the same payload.
+    Ping(Vec<u8>),
+    /// The spectator closed the connection, or it failed.
+    Closed,
+}
+
+/// A game hosted by the server, with the channels of the spectators following its events.
+struct HostedGame {
+    game: ServerGame,
+    spectators: Vec<Sender<SpectatorMessage>>,
+    move_start: Instant,
+}
+
+impl HostedGame {
+    /// Creates a new hosted game, with the given time control for both sides.
+    fn new(time_control: Option<TimeControl>) -> HostedGame {
+        let mut game = Game::new((), ());
+        if let Some(time_control) = time_control {
+            game.set_time_controls(time_control, time_control);
+        }
+        HostedGame {
+            game,
+            spectators: Vec::new(),
+            move_start: Instant::now(),
+        }
+    }
+
+    /// Applies an action to the game and broadcasts the events of the transition it made.
+    fn transition<T, F: FnOnce(&mut ServerGame) -> T>(&mut self, action: F) -> T {
+        let turn = *self.game.get_current_turn();
+        let moves = self.game.get_moves();
+        let result = self.game.get_result();
+        let output = action(&mut self.game);
+        if self.game.get_moves() != moves {
+            self.move_start = Instant::now();
+        }
+        let events = transition_events(&turn, &moves, result, &self.game);
+        self.spectators.retain(|spectator| events.iter().all(|event| spectator.send(SpectatorMessage::Event(event.clone())).is_ok()));
+        if self.game.get_result().is_some() {
+            // Spectators are dropped once they have been sent the end of the game.
+            for spectator in self.spectators.drain(..) {
+                let _ = spectator.send(SpectatorMessage::End);
+            }
+        }
+        output
+    }
+
+    /// Plays a move, charging the time spent on it to the clock of the side to move, if the game is timed.
+    fn play(&mut self, coord: Coord) -> Result<()> {
+        let elapsed = self.move_start.elapsed();
+        self.transition(|game| {
+            let side = game.get_current_state().ok_or(::ReversiError::EndedGame(*game.get_current_turn()))?;
+            // Illegal moves are rejected before charging the clock, which keeps running.
+            game.get_current_turn().check_move(coord)?;
+            game.charge_clock(side, elapsed)?;
+            game.make_move(coord)
+        })
+    }
+
+    /// Ends the game if the side to move has run out of time while thinking.
+    fn check_time(&mut self) {
+        let side = match self.game.get_current_state() {
+            Some(side) if self.game.get_result().is_none() => side,
+            _ => return,
+        };
+        let elapsed = self.move_start.elapsed();
+        if self.game.get_clock(side).is_some_and(|clock| clock.get_time_left() < elapsed) {
+            // The error only reports the timeout, which the transition's events already describe.
+            let _ = self.transition(|game| game.charge_clock(side, elapsed));
+        }
+    }
+}
+
+/// A server hosting games, identified by increasing numbers from 1.
+pub struct Server {
+    games: Mutex<BTreeMap<u64, HostedGame>>,
+    engine: String,
+}
+
+impl Server {
+    /// Creates a new server, with no games and a `medium` engine (see `Engine::from_spec`).
+    pub fn new() -> Server {
+        Server {
+            games: Mutex::new(BTreeMap::new()),
+            engine: "medium".to_string(),
+        }
+    }
+
+    /// Sets the engine playing when none is given, as described for `Engine::from_spec`.
+    /// It fails if the description is invalid.
+    pub fn set_engine(&mut self, spec: &str) -> io::Result<()> {
+        Engine::from_spec(spec)?;
+        self.engine = spec.to_string();
+        Ok(())
+    }
+
+    /// Handles a request and returns the response.
+    /// Requests for events are answered with an error, as they need the connection (see `serve`).
+    pub fn handle(&self, request: &Request) -> Response {
+        let path = request.path.split('?').next().unwrap_or("");
+        let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["games"]) => return self.create_game(&request.body),
+            ("POST", ["games", id, "engine-move"]) => return match id.parse() {
+                Ok(id) => self.engine_move(id, &request.body),
+                Err(_) => Response::error(404, "Unknown game"),
+            },
+            _ => {}
+        }
+        let mut games = self.games.lock().expect("No thread panicked while holding the lock");
+        match (request.method.as_str(), segments.as_slice()) {
+            (method, ["games", id, rest @ ..]) => {
+                let id = match id.parse::<u64>() {
+                    Ok(id) => id,
+                    Err(_) => return Response::error(404, "Unknown game"),
+                };
+                let hosted = match games.get_mut(&id) {
+                    Some(hosted) => hosted,
+                    None => return Response::error(404, "Unknown game"),
+                };
+                hosted.check_time();
+                match (method, rest) {
+                    ("GET", []) => Response::json(200, game_state(id, &hosted.game)),
+                    ("POST", ["moves"]) => {
+                        let coord = match json_field(&request.body, "move").map(|coord| coord.parse::<Coord>()) {
+                            Some(Ok(coord)) => coord,
+                            _ => return Response::error(400, "Expected a move such as {\"move\": \"f5\"}"),
+                        };
+                        match hosted.play(coord) {
+                            Ok(()) => Response::json(200, game_state(id, &hosted.game)),
+                            Err(::ReversiError::TimeOut(side)) => Response::error(409, &format!("{} has run out of time", side_name(side))),
+                            Err(_) => Response::error(409, &format!("Illegal move {}", coord)),
+                        }
+                    }
+                    ("POST", ["undo"]) => match hosted.transition(|game| game.undo()) {
+                        Ok(()) => Response::json(200, game_state(id, &hosted.game)),
+                        Err(::ReversiError::EndedGame(_)) => Response::error(409, "The game is over"),
+                        Err(_) => Response::error(409, "Nothing to undo"),
+                    },
+                    ("GET", ["transcript"]) => Response {
+                        status: 200,
+                        content_type: "text/plain",
+                        body: hosted.game.get_moves().iter().map(|coord| coord.to_string()).collect(),
+                    },
+                    ("GET", ["events"]) => Response::error(400, "Expected a WebSocket upgrade"),
+                    _ => Response::error(404, "Unknown endpoint"),
+                }
+            }
+            _ => Response::error(404, "Unknown endpoint"),
+        }
+    }
+
+    /// Creates a game, timed if the body gives a time control within the server's limits.
+    fn create_game(&self, body: &str) -> Response {
+        let time_control = match json_field(body, "time") {
+            Some(time) => {
+                let increment = json_field(body, "increment").unwrap_or_else(|| "0".to_string());
+                // Infinite, huge or negative values cannot make a duration.
+                let seconds = |value: &str| value.parse::<f64>().ok().filter(|seconds| (0.0..=MAX_REQUEST_TIME).contains(seconds));
+                match (seconds(&time), seconds(&increment)) {
+                    (Some(time), Some(increment)) if time > 0.0 => Some(TimeControl::Fischer {
+                        base: Duration::from_secs_f64(time),
+                        increment: Duration::from_secs_f64(increment),
+                    }),
+                    _ => return Response::error(400, "Expected a time control such as {\"time\": 300, \"increment\": 2}, \
+                                                      of up to a day"),
+                }
+            }
+            None => None,
+        };
+        let mut games = self.games.lock().expect("No thread panicked while holding the lock");
+        let id = games.keys().next_back().map_or(1, |id| id + 1);
+        let hosted = HostedGame::new(time_control);
+        let response = Response::json(201, game_state(id, &hosted.game));
+        games.insert(id, hosted);
+        response
+    }
+
+    /// Has an engine play in a game. The search runs without holding the lock on the games,
+    /// and its move is only played if the game has not changed in the meantime.
+    fn engine_move(&self, id: u64, body: &str) -> Response {
+        let mut engine = match json_field(body, "engine") {
+            Some(spec) => match request_engine(&spec) {
+                Some(engine) => engine,
+                None => return Response::error(400, &format!("Invalid engine {}: expected random, easy, medium, hard, \
+                                                              search:DEPTH up to {} or mcts:ITERATIONS up to {}",
+                                                             spec, MAX_REQUEST_DEPTH, MAX_REQUEST_ITERATIONS)),
+            },
+            None => Engine::from_spec(&self.engine).expect("The server's engine was checked"),
+        };
+        let (turn, moves) = {
+            let mut games = self.games.lock().expect("No thread panicked while holding the lock");
+            let hosted = match games.get_mut(&id) {
+                Some(hosted) => hosted,
+                None => return Response::error(404, "Unknown game"),
+            };
+            hosted.check_time();
+            if hosted.game.get_result().is_some() {
+                return Response::error(409, "The game is over");
+            }
+            (*hosted.game.get_current_turn(), hosted.game.get_moves())
+        };
+        let coord = match engine.choose_move(&turn, 0.0, &mut rand::thread_rng()) {
+            Some((coord, _)) => coord,
+            None => return Response::error(500, "The engine found no move"),
+        };
+        let mut games = self.games.lock().expect("No thread panicked while holding the lock");
+        let hosted = match games.get_mut(&id) {
+            Some(hosted) => hosted,
+            None => return Response::error(404, "Unknown game"),
+        };
+        if hosted.game.get_moves() != moves {
+            return Response::error(409, "The game has changed during the engine's search");
+        }
+        match hosted.play(coord) {
+            Ok(()) => Response::json(200, game_state(id, &hosted.game)),
+            Err(::ReversiError::TimeOut(_)) => Response::error(409, "The engine has run out of time"),
+            Err(_) => Response::error(409, "The game is over"),
+        }
+    }
+
+    /// Serves requests from the given listener, one connection per request and one thread per connection,
+    /// up to `MAX_CONNECTIONS` at once. WebSocket connections to `/games/ID/events` are kept open to stream
+    /// the game's events. Connections which cannot be accepted are reported on standard error, and the server
+    /// goes on serving: it never returns.
+    pub fn serve(&self, listener: TcpListener) {
+        let connections = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        eprintln!("Cannot accept a connection: {}", err);
+                        thread::sleep(ACCEPT_RETRY_DELAY);
+                        continue;
+                    }
+                };
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    let _ = Response::error(503, "Too many connections").write_to(&mut &stream);
+                    continue;
+                }
+                let connections = &connections;
+                scope.spawn(move || {
+                    // Failed connections only concern their client.
+                    let _ = self.handle_connection(stream);
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        })
+    }
+
+    /// Reads a request from a connection and writes back the response, or streams events.
+    fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let request = match Request::read_from(&mut reader) {
+            Ok(request) => request,
+            Err(err) => return Response::error(400, &err.to_string()).write_to(&mut &stream),
+        };
+        let segments: Vec<&str> = request.path.split('/').filter(|segment| !segment.is_empty()).collect();
+        match (request.method.as_str(), segments.as_slice(), request.get_header("sec-websocket-key")) {
+            ("GET", ["games", id, "events"], Some(key)) => match id.parse() {
+                Ok(id) => self.stream_events(id, key, stream),
+                Err(_) => Response::error(404, "Unknown game").write_to(&mut &stream),
+            },
+            _ => self.handle(&request).write_to(&mut &stream),
+        }
+    }
+
+    /// Completes the WebSocket handshake, then sends the game's state and each of its events as a text message,
+    /// until the game is over or the spectator closes the connection.
+    fn stream_events(&self, id: u64, key: &str, mut stream: TcpStream) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        {
+            let mut games = self.games.lock().expect("No thread panicked while holding the lock");
+            let hosted = match games.get_mut(&id) {
+                Some(hosted) => hosted,
+                None => return Response::error(404, "Unknown game").write_to(&mut stream),
+            };
+            hosted.check_time();
+            let state = format!("{{\"type\":\"state\",\"state\":{}}}", game_state(id, &hosted.game));
+            sender.send(SpectatorMessage::Event(state)).expect("The receiver is alive");
+            if hosted.game.get_result().is_some() {
+                sender.send(SpectatorMessage::End).expect("The receiver is alive");
+            } else {
+                hosted.spectators.push(sender.clone());
+            }
+        }
+        write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+               accept_key(key))?;
+        // The spectator's frames are read on their own thread, which forwards them to this one.
+        let mut reader = BufReader::new(stream.try_clone()?);
+        thread::spawn(move || loop {
+            let message = match read_frame(&mut reader) {
+                Ok((OPCODE_PING, payload)) => SpectatorMessage::Ping(payload),
+                Ok((OPCODE_CLOSE, _)) | Err(_) => SpectatorMessage::Closed,
+                Ok(_) => continue,
+            };
+            let closed = matches!(message, SpectatorMessage::Closed);
+            if sender.send(message).is_err() || closed {
+                break;
+            }
+        });
+        let result = forward_events(&receiver, &mut stream);
+        // Stops the reading thread, if the spectator is still connected.
+        let _ = stream.shutdown(Shutdown::Both);
+        result
+    }
+}
+
+impl Default for Server {
+    fn default() -> Server {
+        Server::new()
+    }
+}
+
+/// Writes the messages for a spectator to its connection, until it is closed. Closing is initiated by the server
+/// at the end of the game, and the spectator is then given some time to answer, as the WebSocket protocol requires.
+fn forward_events(receiver: &Receiver<SpectatorMessage>, stream: &mut TcpStream) -> io::Result<()> {
+    let mut closing = false;
+    for message in receiver {
+        match message {
+            SpectatorMessage::Event(event) => write_frame(stream, OPCODE_TEXT, event.as_bytes(), None)?,
+            SpectatorMessage::Ping(payload) => write_frame(stream, OPCODE_PONG, &payload, None)?,
+            SpectatorMessage::End => {
+                write_frame(stream, OPCODE_CLOSE, &[], None)?;
+                stream.set_read_timeout(Some(CLOSE_TIMEOUT))?;
+                closing = true;
+            }
+            SpectatorMessage::Closed => {
+                if !closing {
+                    write_frame(stream, OPCODE_CLOSE, &[], None)?;
+                }
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the state of a game as a JSON object (see the module's documentation).
+pub fn game_state(id: u64, game: &ServerGame) -> String {
+    let turn = game.get_current_turn();
+    let board: Vec<String> = (0..BOARD_SIZE)
+        .map(|row| {
+            let cells: String = (0..BOARD_SIZE)
+                .map(|col| match *turn.get_board().get_cell(Coord::new(row, col)).expect("The coordinate is on the board") {
+                    Some(disk) if disk.get_side() == ::Side::Dark => 'X',
+                    Some(_) => 'O',
+                    None => '.',
+                })
+                .collect();
+            json_string(&cells)
+        })
+        .collect();
+    let coords = |coords: &[Coord]| coords.iter().map(|coord| json_string(&coord.to_string())).collect::<Vec<_>>().join(",");
+    let result = game.get_result().map_or("null".to_string(), |result| json_string(result_name(&result)));
+    let clocks = match (game.get_clock(::Side::Dark), game.get_clock(::Side::Light)) {
+        (Some(dark), Some(light)) => clocks_json(dark, light),
+        _ => "null".to_string(),
+    };
+    format!("{{\"id\":{},\"board\":[{}],\"side\":{},\"legal_moves\":[{}],\"score\":{},\"moves\":[{}],\"result\":{},\"clocks\":{}}}",
+            id,
+            board.join(","),
+            turn.get_state().map_or("null".to_string(), |side| json_string(side_name(side))),
+            coords(&turn.get_legal_moves()),
+            score_json(turn),
+            coords(&game.get_moves()),
+            result,
+            clocks)
+}
+
+/// Returns the events of a game's transition from the given turn, moves and result to its current state
+/// (see the module's documentation).
+fn transition_events(turn: &Turn, moves: &[Coord], result: Option<GameResult>, game: &ServerGame) -> Vec<String> {
+    let mut events = Vec::new();
+    let current_moves = game.get_moves();
+    if current_moves.len() > moves.len() {
+        let mut turn = *turn;
+        for &coord in &current_moves[moves.len()..] {
+            let side = turn.get_state().expect("Played moves are legal");
+            let before = *turn.get_board();
+            turn.make_move(coord).expect("Played moves are legal");
+            let flips: Vec<String> = (0..NUM_CELLS)
+                .map(|index| Coord::new(index / BOARD_SIZE, index % BOARD_SIZE))
+                .filter(|&coord| match (before.get_cell(coord), turn.get_board().get_cell(coord)) {
+                    (Ok(&Some(old)), Ok(&Some(new))) => old.get_side() != new.get_side(),
+                    _ => false,
+                })
+                .map(|coord| json_string(&coord.to_string()))
+                .collect();
+            events.push(format!("{{\"type\":\"move\",\"side\":{},\"move\":{},\"flips\":[{}],\"score\":{}}}",
+                                json_string(side_name(side)), json_string(&coord.to_string()), flips.join(","), score_json(&turn)));
+            if turn.get_state() == Some(side) {
+                events.push(format!("{{\"type\":\"pass\",\"side\":{}}}", json_string(side_name(side.opposite()))));
+            }
+        }
+        if let (Some(dark), Some(light)) = (game.get_clock(::Side::Dark), game.get_clock(::Side::Light)) {
+            events.push(format!("{{\"type\":\"clock\",{}", &clocks_json(dark, light)[1..]));
+        }
+    } else if current_moves.len() < moves.len() {
+        let undone: Vec<String> = moves[current_moves.len()..].iter().rev().map(|coord| json_string(&coord.to_string())).collect();
+        let side = game.get_current_state().map_or("null".to_string(), |side| json_string(side_name(side)));
+        events.push(format!("{{\"type\":\"undo\",\"moves\":[{}],\"side\":{}}}", undone.join(","), side));
+    }
+    if let (None, Some(current_result)) = (result, game.get_result()) {
+        let reason = match current_result {
+            GameResult::WinByDisks(..) | GameResult::Draw => "disks",
+            GameResult::WinByResignation(_) => "resignation",
+            GameResult::WinByTimeout(_) => "timeout",
+            GameResult::WinByForfeit(_) => "forfeit",
+            GameResult::DrawByAgreement => "agreement",
+        };
+        events.push(format!("{{\"type\":\"end\",\"result\":{},\"reason\":{},\"score\":{}}}",
+                            json_string(result_name(&current_result)), json_string(reason), score_json(game.get_current_turn())));
+    }
+    events
+}
+
+/// Creates an engine given in a request: `random`, a strength or a search or MCTS engine within the server's
+/// limits. Weights files cannot be given, as they would be read from the server's disk.
+fn request_engine(spec: &str) -> Option<Engine> {
+    let mut tokens = spec.splitn(3, ':');
+    let valid = match (tokens.next(), tokens.next(), tokens.next()) {
+        (Some("random"), None, None) | (Some("easy"), None, None) | (Some("medium"), None, None) | (Some("hard"), None, None) => true,
+        (Some("search"), Some(depth), None) => depth.parse().is_ok_and(|depth| (1..=MAX_REQUEST_DEPTH).contains(&depth)),
+        (Some("mcts"), Some(iterations), None) => iterations.parse().is_ok_and(|iterations| (1..=MAX_REQUEST_ITERATIONS).contains(&iterations)),
+        _ => false,
+    };
+    if valid {
+        Engine::from_spec(spec).ok()
+    } else {
+        None
+    }
+}
+
+/// Returns the disk count of a turn as a JSON object.
+fn score_json(turn: &Turn) -> String {
+    let (dark, light) = turn.get_score();
+    format!("{{\"dark\":{},\"light\":{}}}", dark, light)
+}
+
+/// Returns the time left on the clocks as a JSON object, in milliseconds.
+fn clocks_json(dark: &Clock, light: &Clock) -> String {
+    format!("{{\"dark\":{},\"light\":{}}}", dark.get_time_left().as_millis(), light.get_time_left().as_millis())
+}
+
+/// Returns the name of a result as used by the API: the winning side or `draw`.
+fn result_name(result: &GameResult) -> &'static str {
+    result.get_winner().map_or("draw", side_name)
+}
+
+/// Returns the name of a side as used by the API.
+fn side_name(side: ::Side) -> &'static str {
+    match side {
+        ::Side::Dark => "dark",
+        ::Side::Light => "light",
+    }
+}
+
+/// Writes a string as a JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Returns the value of a field of a flat JSON object, such as `{"move": "f5"}`, with strings unquoted.
+/// It returns `None` if the field is missing, or if the JSON is not a flat object (see `json_object`).
+pub fn json_field(json: &str, name: &str) -> Option<String> {
+    json_object(json)?.into_iter().find(|(key, _)| key == name).map(|(_, value)| value)
+}
+
+/// Parses a flat JSON object, whose values are strings, numbers, booleans or `null`, into its keys and values,
+/// with strings unquoted. It returns `None` if the JSON is not such an object.
+pub fn json_object(json: &str) -> Option<Vec<(String, String)>> {
+    let mut fields = Vec::new();
+    let mut rest = json.trim().strip_prefix('{')?.trim_start();
+    if !rest.starts_with('}') {
+        loop {
+            let (key, after) = json_string_prefix(rest)?;
+            rest = after.trim_start().strip_prefix(':')?.trim_start();
+            let value = if rest.starts_with('"') {
+                let (value, after) = json_string_prefix(rest)?;
+                rest = after;
+                value
+            } else {
+                let end = rest.find(|c: char| c == ',' || c == '}' || c.is_whitespace()).unwrap_or(rest.len());
+                let literal = &rest[..end];
+                let number = literal.parse::<f64>().is_ok() && literal.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+                if !number && !["true", "false", "null"].contains(&literal) {
+                    return None;
+                }
+                rest = &rest[end..];
+                literal.to_string()
+            };
+            fields.push((key, value));
+            match rest.trim_start().strip_prefix(',') {
+                Some(after) => rest = after.trim_start(),
+                None => break,
+            }
+        }
+    }
+    match rest.trim_start().strip_prefix('}') {
+        Some(end) if end.trim().is_empty() => Some(fields),
+        _ => None,
+    }
+}
+
+/// Parses the JSON string literal starting the given text, returning its value and the rest of the text.
+fn json_string_prefix(text: &str) -> Option<(String, &str)> {
+    let body = text.strip_prefix('"')?;
+    let mut chars = body.char_indices();
+    let mut value = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &body[index + 1..])),
+            '\\' => value.push(match chars.next()?.1 {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hex = chars.by_ref().take(4).map(|(_, c)| c).collect::<String>();
+                    char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                }
+                _ => return None,
+            }),
+            c if (c as u32) < 0x20 => return None,
+            c => value.push(c),
+        }
+    }
+    None
+}
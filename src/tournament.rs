@@ -0,0 +1,402 @@
+//! Implementation of round-robin and Swiss tournaments, with standings and crosstables.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use game::*;
+use arena::play_game;
+
+/// The number of disks awarded for a bye.
+const BYE_DISKS: u32 = 32;
+
+/// How many pairs a Swiss pairing tries before giving up on avoiding rematches.
+const MAX_PAIRING_STEPS: usize = 100_000;
+
+/// How players are paired.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TournamentSystem {
+    /// Every player meets every other player once.
+    RoundRobin,
+    /// The given number of rounds, where players are paired with players on the same score they have not met yet.
+    /// When that is not possible, players are paired with those they have met the fewest times.
+    Swiss(usize),
+}
+
+/// A game to be played in a tournament, between the players with the given indexes in the roster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pairing {
+    pub dark: usize,
+    pub light: usize,
+}
+
+/// The result of a game, together with its final disk count as `(dark, light)`.
+type Outcome = (GameResult, (u8, u8));
+
+/// A round of a tournament: its games (with their outcome, once known) and the player with a bye.
+#[derive(Debug, Clone, Default)]
+struct Round {
+    games: Vec<(Pairing, Option<Outcome>)>,
+    bye: Option<usize>,
+}
+
+/// A player's position in the standings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Standing {
+    /// The player's index in the roster.
+    pub player: usize,
+    /// One point per win (bye included) and half a point per draw.
+    pub points: f64,
+    /// The Brightwell quotient: the player's disk count plus `c` times the sum of its opponents' points,
+    /// where `c` is half the number of rounds, rounded up.
+    pub brightwell: f64,
+    /// The total number of disks of the player, with empty squares awarded to the winner of games played to the end.
+    pub disks: u32,
+}
+
+/// A tournament between the players of a roster, known by their names.
+/// Games can be played by engines, or played elsewhere and recorded.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    names: Vec<String>,
+    system: TournamentSystem,
+    rounds: Vec<Round>,
+}
+
+impl Tournament {
+    /// Creates a new tournament between the given players. No round is paired yet.
+    pub fn new(names: Vec<String>, system: TournamentSystem) -> Tournament {
+        Tournament {
+            names,
+            system,
+            rounds: Vec::new(),
+        }
+    }
+
+    /// Returns the names of the players.
+    #[inline(always)]
+    pub fn get_names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the number of rounds of the tournament.
+    pub fn get_total_rounds(&self) -> usize {
+        match self.system {
+            // With an odd number of players, everybody has a bye once.
+            TournamentSystem::RoundRobin => (self.names.len() + self.names.len() % 2).saturating_sub(1),
+            TournamentSystem::Swiss(rounds) => rounds,
+        }
+    }
+
+    /// Returns the number of rounds paired so far.
+    #[inline(always)]
+    pub fn get_paired_rounds(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// Returns the games of the current round which are still waiting for their result.
+    pub fn get_pending(&self) -> Vec<Pairing> {
+        self.rounds.last()
+            .map(|round| round.games.iter().filter(|&&(_, result)| result.is_none()).map(|&(pairing, _)| pairing).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the games played so far, in order, with their result and final disk count (as `(dark, light)`).
+    pub fn get_results(&self) -> Vec<(Pairing, GameResult, (u8, u8))> {
+        self.rounds.iter()
+            .flat_map(|round| round.games.iter())
+            .filter_map(|&(pairing, outcome)| outcome.map(|(result, score)| (pairing, result, score)))
+            .collect()
+    }
+
+    /// Returns whether every round has been paired and played.
+    pub fn is_over(&self) -> bool {
+        self.rounds.len() >= self.get_total_rounds() && self.get_pending().is_empty()
+    }
+
+    /// Pairs the next round, returning its games. Colours are given so as to balance the games played as Dark and as Light.
+    /// It returns `None` if the current round is not over yet, or if the tournament is over.
+    pub fn pair_next_round(&mut self) -> Option<Vec<Pairing>> {
+        if !self.get_pending().is_empty() || self.rounds.len() >= self.get_total_rounds() {
+            return None;
+        }
+        let (pairs, bye) = match self.system {
+            TournamentSystem::RoundRobin => self.round_robin_pairs(self.rounds.len()),
+            TournamentSystem::Swiss(_) => self.swiss_pairs(),
+        };
+        let games: Vec<Pairing> = pairs.into_iter().map(|(first, second)| self.assign_colours(first, second)).collect();
+        self.rounds.push(Round {
+            games: games.iter().map(|&pairing| (pairing, None)).collect(),
+            bye,
+        });
+        Some(games)
+    }
+
+    /// Records the result and final disk count (as `(dark, light)`) of a pending game of the current round.
+    /// It returns `false` if there is no such game.
+    pub fn record_result(&mut self, pairing: Pairing, result: GameResult, score: (u8, u8)) -> bool {
+        let game = self.rounds.last_mut()
+            .and_then(|round| round.games.iter_mut().find(|game| game.0 == pairing && game.1.is_none()));
+        match game {
+            Some(game) => {
+                game.1 = Some((result, score));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plays the pending games of the current round, with the players given in roster order.
+    pub fn play_round(&mut self, players: &[&dyn IsPlayer<()>]) {
+        assert_eq!(players.len(), self.names.len(), "Every player of the roster has to be given");
+        for pairing in self.get_pending() {
+            let record = play_game(players[pairing.dark], players[pairing.light], ::Side::Dark, &[], None);
+            self.record_result(pairing, record.result, record.score);
+        }
+    }
+
+    /// Pairs and plays all the remaining rounds, with the players given in roster order.
+    pub fn play(&mut self, players: &[&dyn IsPlayer<()>]) {
+        self.play_round(players);
+        while self.pair_next_round().is_some() {
+            self.play_round(players);
+        }
+    }
+
+    /// Returns the standings, sorted by points, then by Brightwell quotient, then by disk count.
+    pub fn get_standings(&self) -> Vec<Standing> {
+        let points = self.get_points();
+        let constant = self.get_total_rounds().div_ceil(2) as f64;
+        let mut standings: Vec<Standing> = (0..self.names.len())
+            .map(|player| {
+                let mut disks = 0;
+                let mut opponents_points = 0.0;
+                for round in &self.rounds {
+                    if round.bye == Some(player) {
+                        disks += BYE_DISKS;
+                    }
+                    for &(pairing, result) in &round.games {
+                        if let Some((result, score)) = result {
+                            if let Some((own, opponent)) = game_outcome(pairing, &result, score, player) {
+                                disks += own as u32;
+                                opponents_points += points[opponent];
+                            }
+                        }
+                    }
+                }
+                Standing {
+                    player,
+                    points: points[player],
+                    brightwell: disks as f64 + constant * opponents_points,
+                    disks,
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.points.partial_cmp(&a.points).expect("Points are never NaN")
+                .then(b.brightwell.partial_cmp(&a.brightwell).expect("Brightwell quotients are never NaN"))
+                .then(b.disks.cmp(&a.disks))
+                .then(a.player.cmp(&b.player))
+        });
+        standings
+    }
+
+    /// Returns the crosstable as text: for each player in standings order, the result of each round
+    /// (opponent's rank, colour as `d` or `l`, and `+`, `=` or `-`), then points, Brightwell quotient and disk count.
+    pub fn get_crosstable(&self) -> String {
+        let standings = self.get_standings();
+        let mut ranks = vec![0; self.names.len()];
+        for (rank, standing) in standings.iter().enumerate() {
+            ranks[standing.player] = rank + 1;
+        }
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max(4);
+        let mut table = String::new();
+        write!(table, "{:>3}  {:<width$}", "#", "Name", width = width).expect("Writing to a string never fails");
+        for round in 0..self.rounds.len() {
+            write!(table, " {:>5}", format!("R{}", round + 1)).expect("Writing to a string never fails");
+        }
+        writeln!(table, " {:>6} {:>7} {:>5}", "Points", "BQ", "Disks").expect("Writing to a string never fails");
+        for (rank, standing) in standings.iter().enumerate() {
+            write!(table, "{:>3}  {:<width$}", rank + 1, self.names[standing.player], width = width).expect("Writing to a string never fails");
+            for round in &self.rounds {
+                let entry = if round.bye == Some(standing.player) {
+                    "bye".to_string()
+                } else {
+                    round.games.iter()
+                        .find(|&&(pairing, _)| pairing.dark == standing.player || pairing.light == standing.player)
+                        .map(|&(pairing, result)| {
+                            let (colour, opponent) = if pairing.dark == standing.player { ('d', pairing.light) } else { ('l', pairing.dark) };
+                            let outcome = match result.map(|(result, _)| result.get_winner()) {
+                                None => '?',
+                                Some(None) => '=',
+                                Some(Some(winner)) if (winner == ::Side::Dark) == (colour == 'd') => '+',
+                                Some(Some(_)) => '-',
+                            };
+                            format!("{}{}{}", ranks[opponent], colour, outcome)
+                        })
+                        .unwrap_or_default()
+                };
+                write!(table, " {:>5}", entry).expect("Writing to a string never fails");
+            }
+            writeln!(table, " {:>6.1} {:>7.1} {:>5}", standing.points, standing.brightwell, standing.disks).expect("Writing to a string never fails");
+        }
+        table
+    }
+
+    /// Returns the points of every player.
+    fn get_points(&self) -> Vec<f64> {
+        let mut points = vec![0.0; self.names.len()];
+        for round in &self.rounds {
+            if let Some(player) = round.bye {
+                points[player] += 1.0;
+            }
+            for &(pairing, result) in &round.games {
+                if let Some((result, _)) = result {
+                    match result.get_winner() {
+                        Some(::Side::Dark) => points[pairing.dark] += 1.0,
+                        Some(::Side::Light) => points[pairing.light] += 1.0,
+                        None => {
+                            points[pairing.dark] += 0.5;
+                            points[pairing.light] += 0.5;
+                        }
+                    }
+                }
+            }
+        }
+        points
+    }
+
+    /// Returns the pairs of the given round of a round-robin by the circle method, with the player having a bye, if any.
+    fn round_robin_pairs(&self, round: usize) -> (Vec<(usize, usize)>, Option<usize>) {
+        // With an odd number of players, the extra slot stands for the bye.
+        let slots = self.names.len() + self.names.len() % 2;
+        let rotating = slots - 1;
+        let slot = |index: usize| if index == 0 { 0 } else { 1 + (index - 1 + round) % rotating };
+        let mut pairs = Vec::new();
+        let mut bye = None;
+        for index in 0..slots / 2 {
+            let (first, second) = (slot(index), slot(slots - 1 - index));
+            if second >= self.names.len() {
+                bye = Some(first);
+            } else if first >= self.names.len() {
+                bye = Some(second);
+            } else {
+                pairs.push((first, second));
+            }
+        }
+        (pairs, bye)
+    }
+
+    /// Returns the pairs of the next Swiss round, with the player having a bye, if any.
+    /// Players are ranked by points, and paired top-down with the best ranked player they have not met yet.
+    /// The bye goes to the lowest ranked player who has not had one yet.
+    fn swiss_pairs(&self) -> (Vec<(usize, usize)>, Option<usize>) {
+        let points = self.get_points();
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|&a, &b| points[b].partial_cmp(&points[a]).expect("Points are never NaN").then(a.cmp(&b)));
+        let mut bye = None;
+        if ranking.len() % 2 == 1 {
+            let had_bye: HashSet<usize> = self.rounds.iter().filter_map(|round| round.bye).collect();
+            let index = ranking.iter().rposition(|player| !had_bye.contains(player)).unwrap_or(ranking.len() - 1);
+            bye = Some(ranking.remove(index));
+        }
+        let mut meetings = HashMap::new();
+        for round in &self.rounds {
+            for &(pairing, _) in &round.games {
+                *meetings.entry((pairing.dark, pairing.light)).or_insert(0) += 1;
+                *meetings.entry((pairing.light, pairing.dark)).or_insert(0) += 1;
+            }
+        }
+        let mut steps = MAX_PAIRING_STEPS;
+        let pairs = pair_unmet(&ranking, &meetings, &mut steps)
+            .unwrap_or_else(|| pair_fewest_meetings(&ranking, &meetings));
+        (pairs, bye)
+    }
+
+    /// Decides the colours of a game: Dark goes to the player who has played fewer games as Dark than as Light,
+    /// or, if they are even, to the one who played Light last (or to the first one).
+    fn assign_colours(&self, first: usize, second: usize) -> Pairing {
+        let balance = |player: usize| -> (i32, Option<::Side>) {
+            let mut balance = 0;
+            let mut last = None;
+            for round in &self.rounds {
+                for &(pairing, _) in &round.games {
+                    if pairing.dark == player {
+                        balance += 1;
+                        last = Some(::Side::Dark);
+                    } else if pairing.light == player {
+                        balance -= 1;
+                        last = Some(::Side::Light);
+                    }
+                }
+            }
+            (balance, last)
+        };
+        let (first_balance, first_last) = balance(first);
+        let (second_balance, second_last) = balance(second);
+        let first_is_dark = if first_balance != second_balance {
+            first_balance < second_balance
+        } else {
+            first_last != Some(::Side::Dark) || second_last == Some(::Side::Dark)
+        };
+        if first_is_dark {
+            Pairing { dark: first, light: second }
+        } else {
+            Pairing { dark: second, light: first }
+        }
+    }
+}
+
+/// Pairs the given players (in ranking order) so that nobody meets an opponent again, if possible.
+/// The search gives up (returning `None`) once it has tried the given number of steps.
+fn pair_unmet(players: &[usize], meetings: &HashMap<(usize, usize), u32>, steps: &mut usize) -> Option<Vec<(usize, usize)>> {
+    let (&first, rest) = match players.split_first() {
+        Some(split) => split,
+        None => return Some(Vec::new()),
+    };
+    for (index, &second) in rest.iter().enumerate() {
+        if meetings.contains_key(&(first, second)) {
+            continue;
+        }
+        if *steps == 0 {
+            return None;
+        }
+        *steps -= 1;
+        let mut others = rest.to_vec();
+        others.remove(index);
+        if let Some(mut pairs) = pair_unmet(&others, meetings, steps) {
+            pairs.insert(0, (first, second));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// Pairs the given players (in ranking order) top-down, each with the best ranked remaining player
+/// among those they have met the fewest times.
+fn pair_fewest_meetings(players: &[usize], meetings: &HashMap<(usize, usize), u32>) -> Vec<(usize, usize)> {
+    let mut remaining = players.to_vec();
+    let mut pairs = Vec::new();
+    while remaining.len() >= 2 {
+        let first = remaining.remove(0);
+        let index = (0..remaining.len())
+            .min_by_key(|&index| meetings.get(&(first, remaining[index])).cloned().unwrap_or(0))
+            .expect("There are remaining players");
+        pairs.push((first, remaining.remove(index)));
+    }
+    pairs
+}
+
+/// Returns the disks of the given player in a game together with its opponent, or `None` if the player did not play the game.
+/// Empty squares are awarded to the winner of games played to the end, as per `GameResult::WinByDisks`.
+fn game_outcome(pairing: Pairing, result: &GameResult, score: (u8, u8), player: usize) -> Option<(u8, usize)> {
+    let (dark, light) = match *result {
+        GameResult::WinByDisks(_, score) => score,
+        _ => score,
+    };
+    if pairing.dark == player {
+        Some((dark, pairing.light))
+    } else if pairing.light == player {
+        Some((light, pairing.dark))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,110 @@
+//! Implementation of the training of pattern-based evaluators from datasets of scored positions.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use turn::*;
+use eval::*;
+
+/// A position together with the score it should be evaluated to
+/// (positive when Light is winning, as in `Turn::get_score_diff`).
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub turn: Turn,
+    pub target: f32,
+}
+
+/// Loads a dataset from a file. See `read_dataset` for the format.
+pub fn load_dataset<P: AsRef<Path>>(path: P) -> io::Result<Vec<Sample>> {
+    read_dataset(BufReader::new(File::open(path)?))
+}
+
+/// Reads a dataset, with one sample per line: the turn (as displayed by `Turn`) followed by the target score.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_dataset<R: BufRead>(reader: R) -> io::Result<Vec<Sample>> {
+    let mut samples = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid sample at line {}", number + 1));
+        let split = line.rfind(char::is_whitespace).ok_or_else(invalid)?;
+        let turn = line[..split].parse().map_err(|_| invalid())?;
+        let target = line[split..].trim().parse().map_err(|_| invalid())?;
+        samples.push(Sample { turn, target });
+    }
+    Ok(samples)
+}
+
+/// Returns the mean squared error of the evaluator over the given samples.
+pub fn mean_squared_error<E: IsEvaluator>(evaluator: &E, samples: &[Sample]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let total: f32 = samples.iter()
+        .map(|sample| (sample.target - evaluator.evaluate(&sample.turn)).powi(2))
+        .sum();
+    total / samples.len() as f32
+}
+
+/// Fits the weights of pattern-based evaluators to datasets by stochastic gradient descent on the squared error.
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    epochs: u32,
+    learning_rate: f32,
+}
+
+impl Trainer {
+    /// Creates a new trainer with default settings: 10 epochs and learning rate 0.1.
+    pub fn new() -> Trainer {
+        Trainer {
+            epochs: 10,
+            learning_rate: 0.1,
+        }
+    }
+
+    /// Sets how many times the whole dataset is gone through.
+    #[inline(always)]
+    pub fn set_epochs(&mut self, epochs: u32) {
+        self.epochs = epochs;
+    }
+
+    /// Sets the size of the gradient descent steps.
+    #[inline(always)]
+    pub fn set_learning_rate(&mut self, learning_rate: f32) {
+        self.learning_rate = learning_rate;
+    }
+
+    /// Trains the evaluator on the samples, in the order they are given.
+    /// It returns the mean squared error of each epoch, computed along the way.
+    pub fn train(&self, evaluator: &mut PatternEvaluator, samples: &[Sample]) -> Vec<f32> {
+        let features: Vec<Vec<usize>> = samples.iter()
+            .map(|sample| evaluator.get_feature_indexes(&sample.turn))
+            .collect();
+        let mut errors = Vec::new();
+        for _ in 0..self.epochs {
+            let mut total = 0.0;
+            for (sample, indexes) in samples.iter().zip(&features) {
+                let weights = evaluator.get_weights_mut();
+                let prediction: f32 = indexes.iter().map(|&index| weights[index]).sum();
+                let error = sample.target - prediction;
+                total += error * error;
+                // Each weight gets its share of the gradient step.
+                let step = self.learning_rate * error / indexes.len() as f32;
+                for &index in indexes {
+                    weights[index] += step;
+                }
+            }
+            errors.push(if samples.is_empty() { 0.0 } else { total / samples.len() as f32 });
+        }
+        errors
+    }
+}
+
+impl Default for Trainer {
+    fn default() -> Trainer {
+        Trainer::new()
+    }
+}
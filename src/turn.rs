@@ -1,5 +1,7 @@
 //! Implementation of Reversi rules to play a turn.
 
+use std::fmt;
+use std::str::FromStr;
 use board::*;
 use ::Result;
 
@@ -8,7 +10,7 @@ pub type State = Option<::Side>;
 
 /// A turn is given by a board and by which player has to move next.
 /// For convenience we also annotate current scores.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Turn {
     board: Board,
     state: State,
@@ -36,6 +38,38 @@ impl Turn {
         }
     }
 
+    /// Creates a turn from a given board, with the given side to move.
+    /// As when making a move, if that side cannot move the turn passes to the other side,
+    /// and if neither can the turn is ended.
+    pub fn new(board: Board, side: ::Side) -> Turn {
+        let mut score_dark = 0;
+        let mut score_light = 0;
+        for row in board.get_board() {
+            for cell in row {
+                match *cell {
+                    Some(disk) if disk.get_side() == ::Side::Dark => score_dark += 1,
+                    Some(_) => score_light += 1,
+                    None => {}
+                }
+            }
+        }
+        let mut turn = Turn {
+            board,
+            state: Some(side),
+            score_dark,
+            score_light,
+        };
+        if turn.get_tempo() == NUM_CELLS as u8 {
+            turn.state = None;
+        } else if !turn.can_move() {
+            turn.state = Some(side.opposite());
+            if !turn.can_move() {
+                turn.state = None;
+            }
+        }
+        turn
+    }
+
     /// Returns the turn's board
     #[inline(always)]
     pub fn get_board(&self) -> &Board {
@@ -72,6 +106,21 @@ impl Turn {
         self.score_light as i16 - self.score_dark as i16
     }
 
+    /// Produces the image of the turn through a symmetry of the board.
+    pub fn transform(&self, symmetry: Symmetry) -> Turn {
+        Turn {
+            board: self.board.transform(symmetry),
+            ..*self
+        }
+    }
+
+    /// Returns the canonical representative of the turn's symmetry class (see `Board::canonical`),
+    /// together with the symmetry mapping the turn to it.
+    pub fn canonical(&self) -> (Turn, Symmetry) {
+        let (board, symmetry) = self.board.canonical();
+        (Turn { board, ..*self }, symmetry)
+    }
+
     /// Returns turn's tempo (how many disks there are on the board).
     #[inline(always)]
     pub fn get_tempo(&self) -> u8 {
@@ -96,6 +145,13 @@ impl Turn {
         false
     }
 
+    /// Checks whether the given side could legally move to the given cell, regardless of whose turn it is.
+    #[inline(always)]
+    pub fn is_legal_for(&self, coord: Coord, side: ::Side) -> bool {
+        self.board.is_empty(coord).unwrap_or(false)
+            && DIRECTIONS.iter().any(|&dir| self.check_move_along_direction(coord, dir, side))
+    }
+
     /// Check whether a given move is legal
     #[inline(always)]
     pub fn check_move (&self, coord: Coord) -> Result<()> {
@@ -135,6 +191,20 @@ impl Turn {
         }
     }
 
+    /// Returns all the legal moves for the side which has to play next.
+    pub fn get_legal_moves(&self) -> Vec<Coord> {
+        let mut moves = Vec::new();
+        for row in 0..BOARD_SIZE {
+            for col in 0..BOARD_SIZE {
+                let coord = Coord::new(row, col);
+                if self.check_move(coord).is_ok() {
+                    moves.push(coord);
+                }
+            }
+        }
+        moves
+    }
+
     /// Current player performs a move, after verifying that it is legal.
     /// It returns either the new turn or the error preventing the move to be performed.
     #[inline(always)]
@@ -258,3 +328,55 @@ impl Turn {
 
 
 }
+
+/// Turns are written as the board's cells, row by row, followed by the side to move:
+/// `X` stands for Dark, `O` for Light and `-` for an empty cell (or for no side to move, if the turn is ended).
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = |side: Option<::Side>| match side {
+            Some(::Side::Dark)  => 'X',
+            Some(::Side::Light) => 'O',
+            None => '-',
+        };
+        for row in self.board.get_board() {
+            for cell in row {
+                write!(f, "{}", symbol(cell.map(|disk| disk.get_side())))?;
+            }
+        }
+        write!(f, " {}", symbol(self.state))
+    }
+}
+
+/// Parses turns in the format they are displayed in.
+/// Ended turns can be given with either side to move, as the actual state is always recomputed.
+impl FromStr for Turn {
+    type Err = ::ReversiError;
+
+    fn from_str(s: &str) -> Result<Turn> {
+        let mut tokens = s.split_whitespace();
+        let (cells, side) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(cells), Some(side), None) if cells.len() == NUM_CELLS => (cells, side),
+            _ => return Err(::ReversiError::InvalidNotation),
+        };
+        let mut board = Board::new([[None; BOARD_SIZE]; BOARD_SIZE]);
+        for (index, symbol) in cells.chars().enumerate() {
+            let coord = Coord::new(index / BOARD_SIZE, index % BOARD_SIZE);
+            match symbol {
+                'X' => board.place_disk(::Side::Dark, coord)?,
+                'O' => board.place_disk(::Side::Light, coord)?,
+                '-' => {}
+                _ => return Err(::ReversiError::InvalidNotation),
+            }
+        }
+        match side {
+            "X" => Ok(Turn::new(board, ::Side::Dark)),
+            "O" => Ok(Turn::new(board, ::Side::Light)),
+            // No side to move is only valid for a position where neither side can move.
+            "-" => match Turn::new(board, ::Side::Dark) {
+                turn if turn.is_end_state() => Ok(turn),
+                _ => Err(::ReversiError::InvalidNotation),
+            },
+            _ => Err(::ReversiError::InvalidNotation),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Match tests
+
+extern crate reversi;
+
+use reversi::board::*;
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::arena::*;
+use reversi::eval::DiskDifference;
+use reversi::engine::Engine;
+use reversi::{Result, ReversiError, Side};
+
+/// A player which always plays the first legal move.
+struct Naive;
+
+impl IsPlayer<()> for Naive {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        turn.get_legal_moves().first().map(|&coord| PlayerAction::Move(coord)).ok_or(ReversiError::EndedGame(*turn))
+    }
+}
+
+/// A player which always attempts the same illegal move.
+struct Cheater;
+
+impl IsPlayer<()> for Cheater {
+    fn make_move(&self, _turn: &Turn) -> Result<PlayerAction<()>> {
+        Ok(PlayerAction::Move(Coord::new(0, 0)))
+    }
+}
+
+#[test]
+fn test_match() {
+    let openings = vec![parse_coords("f5d6").unwrap(), parse_coords("f5f6").unwrap()];
+    let mut game_match = Match::new(|| Engine::from_spec("search:2").unwrap(), || &Naive, 8);
+    game_match.set_openings(openings);
+    game_match.set_threads(3);
+    let records = game_match.play();
+    assert_eq!(records.len(), 8);
+    for (index, record) in records.iter().enumerate() {
+        assert_eq!(record.side, if index % 2 == 0 { Side::Dark } else { Side::Light });
+        let opening = if (index / 2) % 2 == 0 { "f5d6" } else { "f5f6" };
+        assert!(record.get_transcript().starts_with(opening));
+    }
+    // Deterministic players play the same games when they are given the same opening and colours.
+    assert_eq!(records[0].moves, records[4].moves);
+
+    let stats = MatchStats::new(&records);
+    assert_eq!(stats.get_games(), 8);
+    assert!(stats.wins > stats.losses);
+    let (low, high) = stats.get_elo_interval();
+    assert!(low <= stats.get_elo_diff() && stats.get_elo_diff() <= high);
+}
+
+#[test]
+fn test_forfeit() {
+    let record = play_game(&Cheater, &Naive, Side::Dark, &[], None);
+    assert_eq!(record.result, GameResult::WinByForfeit(Side::Light));
+    assert_eq!(record.get_points(), 0.0);
+    assert!(record.moves.is_empty());
+}
+
+#[test]
+fn test_elo() {
+    assert_eq!(elo_diff(0.5), 0.0);
+    assert!((elo_diff(0.75) - 190.85).abs() < 0.01);
+    assert!(elo_diff(1.0).is_infinite());
+}
+
+#[test]
+fn test_balanced_openings() {
+    assert_eq!(balanced_openings(1, &DiskDifference, 64.0).len(), 1);
+    assert_eq!(balanced_openings(2, &DiskDifference, 64.0).len(), 3);
+    let openings = balanced_openings(4, &DiskDifference, 0.0);
+    assert!(!openings.is_empty());
+    for opening in &openings {
+        let mut turn = Turn::first_turn();
+        for &coord in opening {
+            turn.make_move(coord).expect("Openings are legal");
+        }
+        assert_eq!(turn.get_score_diff(), 0);
+    }
+}
+
+#[test]
+fn test_sprt() {
+    let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+    let (lower, upper) = sprt.get_bounds();
+    assert!((upper - 2.944).abs() < 0.001 && (lower + 2.944).abs() < 0.001);
+    assert_eq!(sprt.get_llr(&[]), 0.0);
+    assert!(Sprt::new(-10.0, 10.0, 0.05, 0.05).get_llr(&[0.5, 0.5]).abs() < 1e-9);
+    assert!(sprt.get_llr(&[1.0; 10]) > upper);
+    assert!(sprt.get_llr(&[1.0, 0.5, 0.75]) > 0.0);
+    assert!(sprt.get_llr(&[0.0, 0.5, 0.25]) < 0.0);
+    // The GSPRT's normal approximation, N (s1 - s0) (2 mean - s0 - s1) / (2 variance), for 8 pairs of mean 19/32 and
+    // variance 111/1024: the regularization of the outcomes does not change it when all of them occur.
+    let scores = [0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 0.75, 0.5];
+    assert!((sprt.get_llr(&scores) - 0.091_905_6).abs() < 1e-6);
+    assert_eq!(sprt.get_verdict(upper), Some(SprtVerdict::H1));
+    assert_eq!(sprt.get_verdict(0.0), None);
+
+    let mut game_match = Match::new(|| Engine::from_spec("search:2").unwrap(), || Engine::Random, 200);
+    game_match.set_openings(balanced_openings(2, &DiskDifference, 64.0));
+    game_match.set_threads(2);
+    let report = game_match.play_sprt(&Sprt::new(0.0, 100.0, 0.05, 0.05));
+    assert_eq!(report.verdict, Some(SprtVerdict::H1));
+    assert!(report.records.len() < 200);
+    assert_eq!(report.records.len() % 2, 0);
+}
@@ -0,0 +1,109 @@
+//! Opening book tests
+
+extern crate rand;
+extern crate reversi;
+
+use rand::{SeedableRng, XorShiftRng};
+use reversi::board::*;
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::book::*;
+use reversi::{Result, ReversiError};
+use std::io::Cursor;
+
+/// A player which always plays the first legal move.
+struct Naive;
+
+impl IsPlayer<()> for Naive {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        turn.get_legal_moves().first().map(|&coord| PlayerAction::Move(coord)).ok_or(ReversiError::EndedGame(*turn))
+    }
+}
+
+#[test]
+fn test_symmetric_lookup() {
+    let mut book = Book::new();
+    book.add_game(&parse_coords("f5d6").unwrap(), 60).unwrap();
+    assert_eq!(book.len(), 2);
+    let mut turn = Turn::first_turn();
+    assert_eq!(book.get_moves(&turn).len(), 1);
+    // e6 is the image of f5 through the diagonal symmetry, hence the book answers with the image of d6.
+    turn.make_move("e6".parse().unwrap()).unwrap();
+    let moves = book.get_moves(&turn);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].get_coord(), "f4".parse().unwrap());
+    assert_eq!(moves[0].get_count(), 1);
+    turn.make_move(moves[0].get_coord()).unwrap();
+    assert!(book.get_moves(&turn).is_empty());
+}
+
+#[test]
+fn test_weighted_choice() {
+    let mut book = Book::new();
+    let games = "f5d6c3\nf5f6\n\nf5d6c5\n";
+    assert_eq!(book.add_games_from(Cursor::new(games), 60).unwrap(), 3);
+    assert!(book.add_games_from(Cursor::new("f5f5"), 60).is_err());
+    let mut turn = Turn::first_turn();
+    turn.make_move("f5".parse().unwrap()).unwrap();
+    let moves = book.get_moves(&turn);
+    assert_eq!(moves.iter().map(BookMove::get_count).sum::<u32>(), 3);
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let picks: Vec<Coord> = (0..100).map(|_| book.choose_move(&turn, &mut rng).unwrap()).collect();
+    let d6 = picks.iter().filter(|&&coord| coord == Coord::new(5, 3)).count();
+    assert!(d6 > 50 && d6 < 100);
+}
+
+#[test]
+fn test_book_file() {
+    let mut book = Book::new();
+    book.add_games_from(Cursor::new("f5d6c3d3c4\nf5f6e6f4"), 4).unwrap();
+    let mut bytes = Vec::new();
+    book.write_to(&mut bytes).unwrap();
+    let loaded = Book::read_from(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(loaded.len(), book.len());
+    let mut turn = Turn::first_turn();
+    turn.make_move("f5".parse().unwrap()).unwrap();
+    assert_eq!(loaded.get_moves(&turn), book.get_moves(&turn));
+    assert!(Book::read_from(&mut Cursor::new(&bytes[1..])).is_err());
+}
+
+#[test]
+fn test_book_player() {
+    let mut book = Book::new();
+    book.add_game(&parse_coords("c4e3").unwrap(), 60).unwrap();
+    let mut player = BookPlayer::new(book.clone(), &Naive, XorShiftRng::from_seed([1, 2, 3, 4]));
+    let mut turn = Turn::first_turn();
+    match IsStatefulPlayer::<()>::make_move(&mut player, &turn).unwrap() {
+        PlayerAction::Move(coord) => assert_eq!(coord, "c4".parse().unwrap()),
+        _ => panic!("The player should play from its book"),
+    }
+    turn.make_move("d3".parse().unwrap()).unwrap();
+    turn.make_move("c3".parse().unwrap()).unwrap();
+    let player = BookPlayer::new(book, Naive, XorShiftRng::from_seed([1, 2, 3, 4]));
+    match IsPlayer::<()>::make_move(&player, &turn).unwrap() {
+        PlayerAction::Move(coord) => assert_eq!(coord, turn.get_legal_moves()[0]),
+        _ => panic!("The player should fall back out of book"),
+    }
+}
+
+#[test]
+fn test_book_player_variety() {
+    let mut book = Book::new();
+    // The three replies to f5 are not symmetric to each other.
+    for &game in &["f5d6", "f5f6", "f5f4"] {
+        book.add_game(&parse_coords(game).unwrap(), 2).unwrap();
+    }
+    let player = BookPlayer::new(book, Naive, XorShiftRng::from_seed([1, 2, 3, 4]));
+    let mut turn = Turn::first_turn();
+    turn.make_move("f5".parse().unwrap()).unwrap();
+    let mut moves = Vec::new();
+    for _ in 0..20 {
+        if let PlayerAction::Move(coord) = IsPlayer::<()>::make_move(&player, &turn).unwrap() {
+            if !moves.contains(&coord) {
+                moves.push(coord);
+            }
+        }
+    }
+    // Successive choices through a shared reference vary.
+    assert!(moves.len() > 1);
+}
@@ -0,0 +1,54 @@
+//! Terminal CLI tests
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the CLI with the given arguments on the given input and returns its output, and whether it succeeded.
+fn run_cli(args: &[&str], input: &str) -> (String, bool) {
+    let mut cli = Command::new(env!("CARGO_BIN_EXE_reversi"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("The CLI can be started");
+    cli.stdin.as_mut().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = cli.wait_with_output().unwrap();
+    (String::from_utf8(output.stdout).unwrap(), output.status.success())
+}
+
+#[test]
+fn test_cli_humans() {
+    let path = env::temp_dir().join("reversi_test_cli_humans.txt");
+    let path = path.to_str().unwrap();
+    let (output, success) = run_cli(&["--dark", "human", "--light", "human"], &format!("f5\nd7\nd6\nundo\nredo\nredo\nsave {}\nquit\n", path));
+    assert!(success);
+    // The responses to each command, split at the prompts.
+    let responses: Vec<&str> = output.split("> ").collect();
+    assert_eq!(responses[2], "Illegal move d7\n");
+    assert!(responses[3].contains(" 6  . . *>O . . . .") && responses[3].contains("Dark (X) 3 - 3 Light (O)\nDark to move"));
+    // Undoing as Dark takes back both moves, and redoing plays them again.
+    assert!(responses[4].contains("Dark (X) 2 - 2 Light (O)\nDark to move"));
+    assert!(responses[0].ends_with(responses[4]));
+    assert_eq!(responses[5], responses[3]);
+    assert_eq!(responses[6], "Nothing to redo\n");
+    assert_eq!(fs::read_to_string(path).unwrap(), "f5d6\n");
+
+    let (output, success) = run_cli(&["--dark", "human", "--light", "human", "--load", path], "hint\nc3\nplayer light easy\nquit\n");
+    assert!(success);
+    fs::remove_file(path).unwrap();
+    assert!(output.contains("Hint: "));
+    assert!(output.contains("Light to move"));
+    assert!(output.contains("Light (easy) plays "));
+}
+
+#[test]
+fn test_cli_engines() {
+    let (output, success) = run_cli(&["--dark", "random", "--light", "search:1"], "");
+    assert!(success);
+    assert!(output.contains("Game over: "));
+    let (output, success) = run_cli(&["--dark", "nobody"], "");
+    assert!(!success);
+    assert!(output.is_empty());
+}
@@ -0,0 +1,25 @@
+//! Engine tests
+
+extern crate reversi;
+extern crate rand;
+
+use reversi::engine::*;
+use reversi::turn::*;
+use rand::{SeedableRng, XorShiftRng};
+
+#[test]
+fn test_engine_specs() {
+    assert!(Engine::from_spec("search").is_err());
+    assert!(Engine::from_spec("search:0").is_err());
+    assert!(Engine::from_spec("mcts:0").is_err());
+    assert!(Engine::from_spec("alien:3").is_err());
+    assert!(matches!(Engine::from_spec("hard").unwrap(), Engine::Search(ref player) if player.get_depth() == 6));
+    assert!(matches!(Engine::from_spec("easy").unwrap(), Engine::Search(ref player) if player.get_depth() == 1));
+    assert_eq!(Engine::from_spec("mcts:10").unwrap().get_score_unit(), ScoreUnit::Probability);
+    assert!(matches!(Player::from_spec("human").unwrap(), Player::Human));
+    assert_eq!(Player::from_spec("medium").unwrap().get_name(), "medium");
+    assert!(Player::from_spec("alien").is_err());
+    let turn = Turn::first_turn();
+    let (coord, score) = Engine::Random.choose_move(&turn, 0.0, &mut XorShiftRng::from_seed([1, 2, 3, 4])).unwrap();
+    assert!(turn.check_move(coord).is_ok() && score == 0.0);
+}
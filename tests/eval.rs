@@ -0,0 +1,61 @@
+//! Evaluation and search tests
+
+extern crate reversi;
+
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::eval::*;
+use reversi::search::*;
+use reversi::{Result, ReversiError, Side};
+use std::env;
+use std::io::Cursor;
+
+/// A player which always plays the first legal move.
+struct Naive;
+
+impl IsPlayer<()> for Naive {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        turn.get_legal_moves().first().map(|&coord| PlayerAction::Move(coord)).ok_or(ReversiError::EndedGame(*turn))
+    }
+}
+
+#[test]
+fn test_pattern_evaluator() {
+    let evaluator = PatternEvaluator::new(4);
+    let mut turn = Turn::first_turn();
+    assert_eq!(evaluator.evaluate(&turn), 0.0);
+    assert_eq!(evaluator.get_stage(&turn), 0);
+    turn.make_move(turn.get_legal_moves()[0]).expect("The move is legal");
+    assert!(evaluator.evaluate(&turn) < 0.0, "Dark is ahead after its first move");
+    assert_eq!(DiskDifference.evaluate(&turn), turn.get_score_diff() as f32);
+}
+
+#[test]
+fn test_weights_file() {
+    let mut evaluator = PatternEvaluator::new(3);
+    let turn = Turn::first_turn();
+    for index in evaluator.get_feature_indexes(&turn) {
+        evaluator.get_weights_mut()[index] += 0.5;
+    }
+    let path = env::temp_dir().join("reversi_test_weights.bin");
+    evaluator.save(&path).expect("Saving weights");
+    let loaded = PatternEvaluator::load(&path).expect("Loading weights");
+    assert_eq!(loaded.get_stages(), 3);
+    assert_eq!(loaded.get_weights(), evaluator.get_weights());
+    assert_eq!(loaded.evaluate(&turn), evaluator.evaluate(&turn));
+
+    let mut bytes = Vec::new();
+    evaluator.write_to(&mut bytes).expect("Writing weights");
+    bytes[0] = b'X';
+    assert!(PatternEvaluator::read_from(&mut Cursor::new(bytes)).is_err());
+}
+
+#[test]
+fn test_search_player_beats_naive_player() {
+    let player = SearchPlayer::new(PatternEvaluator::new(1), 2);
+    let mut game = Game::new(&Naive, &player);
+    while !game.is_endgame() {
+        game.play_turn().expect("The game is not over yet");
+    }
+    assert_eq!(game.get_result().and_then(|result| result.get_winner()), Some(Side::Light));
+}
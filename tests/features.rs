@@ -0,0 +1,73 @@
+//! Positional features tests
+
+extern crate reversi;
+
+use reversi::board::*;
+use reversi::turn::*;
+use reversi::features::*;
+use reversi::Side;
+
+#[test]
+fn test_first_turn_features() {
+    let features = Turn::first_turn().get_features();
+    assert_eq!(features, Features {
+        mobility: (4, 4),
+        potential_mobility: (10, 10),
+        frontier_disks: (2, 2),
+        stable_disks: (0, 0),
+        corners: (0, 0),
+        x_squares: (0, 0),
+        c_squares: (0, 0),
+        odd_empty_regions: 0,
+        edges: [0; 4],
+    });
+}
+
+#[test]
+fn test_edge_features() {
+    let turn: Turn = concat!(
+        "XXXXXXXX",
+        "-O------",
+        "--------",
+        "---OX---",
+        "---XO---",
+        "--------",
+        "-------O",
+        "------O-",
+        " O").parse().expect("Valid notation");
+    assert_eq!(turn.get_stable_disks(Side::Dark), 8);
+    assert_eq!(turn.get_stable_disks(Side::Light), 0);
+    assert_eq!(turn.get_corner_occupancy(Side::Dark), 2);
+    assert_eq!(turn.get_x_square_occupancy(Side::Light), 1);
+    assert_eq!(turn.get_c_square_occupancy(Side::Dark), 2);
+    assert_eq!(turn.get_c_square_occupancy(Side::Light), 2);
+    assert_eq!(turn.get_edge_configurations()[0], 3usize.pow(8) / 2);
+    assert_eq!(turn.get_edge_configurations()[1], 2 * 3);
+    assert_eq!(turn.get_edge_configurations()[3], 3usize.pow(7) + 2 * 3);
+    assert_eq!(turn.get_empty_regions(), vec![49]);
+    assert_eq!(turn.get_odd_empty_regions(), 1);
+    assert_eq!(turn.get_mobility(Side::Light), turn.get_legal_moves().len() as u8);
+}
+
+#[test]
+fn test_stability_modes() {
+    let checkerboard: String = (0..64).map(|index| if (index / 8 + index % 8) % 2 == 0 { 'X' } else { 'O' }).collect();
+    let turn: Turn = format!("{} -", checkerboard).parse().expect("Valid notation");
+    assert!(turn.is_end_state());
+    assert_eq!(turn.stable_disks(Side::Dark, StabilityMode::Extended).len(), 32);
+    assert_eq!(turn.stable_disks(Side::Light, StabilityMode::Extended).len(), 32);
+    assert_eq!(turn.stable_disks(Side::Dark, StabilityMode::Conservative), vec![Coord::new(0, 0), Coord::new(7, 7)]);
+    assert_eq!(turn.stable_disks(Side::Light, StabilityMode::Conservative), vec![Coord::new(0, 7), Coord::new(7, 0)]);
+
+    // A full top edge in mid-game: all its disks are stable, but only the corners are anchored to same-side disks.
+    let turn: Turn = format!("XOXOXOXO{}---OX------XO---{} X", "-".repeat(16), "-".repeat(24)).parse().expect("Valid notation");
+    let top_edge = |first_col: usize| (0..4).map(|col| Coord::new(0, first_col + 2 * col)).collect::<Vec<Coord>>();
+    assert_eq!(turn.stable_disks(Side::Dark, StabilityMode::Extended), top_edge(0));
+    assert_eq!(turn.stable_disks(Side::Light, StabilityMode::Extended), top_edge(1));
+    assert_eq!(turn.stable_disks(Side::Dark, StabilityMode::Conservative), vec![Coord::new(0, 0)]);
+    assert_eq!(turn.stable_disks(Side::Light, StabilityMode::Conservative), vec![Coord::new(0, 7)]);
+    assert_eq!(turn.get_stable_disks(Side::Dark), 4);
+
+    let turn = Turn::first_turn();
+    assert!(turn.stable_disks(Side::Dark, StabilityMode::Extended).is_empty());
+}
@@ -0,0 +1,350 @@
+//! Game tests
+
+extern crate reversi;
+
+use reversi::board::*;
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::clock::*;
+use reversi::ponder::*;
+use reversi::{Result, ReversiError, Side};
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+/// A player which resigns straight away.
+struct Quitter;
+
+impl IsPlayer<()> for Quitter {
+    fn make_move(&self, _turn: &Turn) -> Result<PlayerAction<()>> {
+        Ok(PlayerAction::Resign)
+    }
+}
+
+/// A player which attempts an illegal move a given number of times before playing a legal one.
+struct Clumsy(Cell<u8>);
+
+impl IsPlayer<()> for Clumsy {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        if self.0.get() > 0 {
+            self.0.set(self.0.get() - 1);
+            Ok(PlayerAction::Move(Coord::new(0, 0)))
+        } else {
+            SlowPlayer(Duration::from_millis(0)).make_move(turn)
+        }
+    }
+}
+
+/// A stateful player keeping track of the game's progress.
+#[derive(Default)]
+struct Recorder {
+    side: Option<Side>,
+    moves: u8,
+    opponent_moves: u8,
+    result: Option<GameResult>,
+}
+
+impl IsStatefulPlayer<()> for Recorder {
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<()>> {
+        self.moves += 1;
+        IsPlayer::make_move(&SlowPlayer(Duration::from_millis(0)), turn)
+    }
+
+    fn on_game_start(&mut self, side: Side, _turn: &Turn) {
+        assert!(self.side.is_none());
+        self.side = Some(side);
+    }
+
+    fn on_opponent_move(&mut self, _coord: Coord, _turn: &Turn) {
+        self.opponent_moves += 1;
+    }
+
+    fn on_game_end(&mut self, result: &GameResult) {
+        assert!(self.result.is_none());
+        self.result = Some(*result);
+    }
+}
+
+/// A player which always plays the first legal move it finds, after waiting for a while.
+struct SlowPlayer(Duration);
+
+impl IsPlayer<()> for SlowPlayer {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        thread::sleep(self.0);
+        first_legal_move(turn).map(PlayerAction::Move).ok_or(ReversiError::EndedGame(*turn))
+    }
+}
+
+/// Returns the first legal move found on the turn's board, if any.
+fn first_legal_move(turn: &Turn) -> Option<Coord> {
+    for row in 0..BOARD_SIZE {
+        for col in 0..BOARD_SIZE {
+            let coord = Coord::new(row, col);
+            if turn.check_move(coord).is_ok() {
+                return Some(coord);
+            }
+        }
+    }
+    None
+}
+
+/// A player which ponders assuming the opponent will play the first legal move.
+#[derive(Default)]
+struct Ponderer {
+    ponder: Option<Ponder<Option<Coord>>>,
+    pondered_move: Option<Coord>,
+    hits: u8,
+}
+
+impl IsStatefulPlayer<()> for Ponderer {
+    fn make_move(&mut self, turn: &Turn) -> Result<PlayerAction<()>> {
+        let coord = self.pondered_move.take().or_else(|| first_legal_move(turn));
+        coord.map(PlayerAction::Move).ok_or(ReversiError::EndedGame(*turn))
+    }
+
+    fn on_opponent_move(&mut self, _coord: Coord, turn: &Turn) {
+        if let Some(ponder) = self.ponder.take() {
+            self.pondered_move = ponder.finish(turn).and_then(|coord| coord);
+            if self.pondered_move.is_some() {
+                self.hits += 1;
+            }
+        }
+    }
+
+    fn start_pondering(&mut self, turn: &Turn) {
+        let mut predicted_turn = *turn;
+        if let Some(coord) = first_legal_move(turn) {
+            predicted_turn.make_move(coord).expect("This move is legal");
+            self.ponder = Some(Ponder::start(predicted_turn, |turn, _| first_legal_move(&turn)));
+        }
+    }
+}
+
+#[test]
+fn test_clocks() {
+    let mut fischer = Clock::new(TimeControl::Fischer { base: Duration::from_secs(10), increment: Duration::from_secs(2) });
+    assert!(fischer.charge(Duration::from_secs(5)));
+    assert_eq!(fischer.get_time_left(), Duration::from_secs(7));
+    assert!(!fischer.charge(Duration::from_secs(8)));
+    assert!(fischer.is_flagged());
+
+    let mut byo_yomi = Clock::new(TimeControl::ByoYomi { main: Duration::from_secs(10), period: Duration::from_secs(5), periods: 2 });
+    assert!(byo_yomi.charge(Duration::from_secs(12)));
+    assert_eq!(byo_yomi.get_periods(), 2);
+    assert!(byo_yomi.charge(Duration::from_secs(7)));
+    assert_eq!(byo_yomi.get_periods(), 1);
+    assert_eq!(byo_yomi.get_time_left(), Duration::from_secs(5));
+    assert!(!byo_yomi.charge(Duration::from_secs(6)));
+}
+
+#[test]
+fn test_loss_on_time() {
+    let player = SlowPlayer(Duration::from_millis(0));
+    let mut game = Game::new(&player, &player);
+    game.set_time_controls(TimeControl::SuddenDeath(Duration::from_secs(10)), TimeControl::SuddenDeath(Duration::from_millis(50)));
+    game.charge_clock(Side::Light, Duration::from_millis(30)).expect("Light has still time left");
+    assert_eq!(game.get_clock(Side::Light).unwrap().get_time_left(), Duration::from_millis(20));
+    match game.charge_clock(Side::Light, Duration::from_millis(30)) {
+        Err(ReversiError::TimeOut(Side::Light)) => {}
+        _ => panic!("Light should have run out of time"),
+    }
+    assert_eq!(game.get_result(), Some(GameResult::WinByTimeout(Side::Dark)));
+    assert!(game.is_endgame());
+    assert!(game.play_turn().is_err());
+
+    // Players are charged the time they spend on their moves, here orders of magnitude apart from their budgets.
+    let fast = SlowPlayer(Duration::from_millis(0));
+    let slow = SlowPlayer(Duration::from_millis(100));
+    let mut game = Game::new(&fast, &slow);
+    game.set_time_controls(TimeControl::SuddenDeath(Duration::from_secs(1000)), TimeControl::SuddenDeath(Duration::from_millis(1)));
+    game.play_turn().expect("Dark has plenty of time");
+    match game.play_turn() {
+        Err(ReversiError::TimeOut(Side::Light)) => {}
+        _ => panic!("Light should have run out of time"),
+    }
+    assert_eq!(game.get_result(), Some(GameResult::WinByTimeout(Side::Dark)));
+}
+
+#[test]
+fn test_resignation() {
+    let player = SlowPlayer(Duration::from_millis(0));
+    let mut game = Game::new(&player, &Quitter);
+    game.play_turn().expect("Dark can move");
+    assert_eq!(game.get_result(), None);
+    game.play_turn().expect("Light can resign");
+    assert_eq!(game.get_result(), Some(GameResult::WinByResignation(Side::Dark)));
+    assert!(game.is_endgame());
+    assert!(game.play_turn().is_err());
+    assert!(game.undo().is_err());
+}
+
+#[test]
+fn test_final_result() {
+    let player = SlowPlayer(Duration::from_millis(0));
+    let mut game = Game::new(&player, &player);
+    while !game.is_endgame() {
+        game.play_turn().expect("The game is not over yet");
+    }
+    let (dark, light) = game.get_current_score();
+    match game.get_result().expect("The game is over") {
+        GameResult::WinByDisks(winner, (final_dark, final_light)) => {
+            assert_eq!(final_dark as usize + final_light as usize, NUM_CELLS);
+            assert_eq!(winner == Side::Dark, dark > light);
+            assert_eq!(final_dark.min(final_light), dark.min(light));
+        }
+        GameResult::Draw => assert_eq!(dark, light),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_illegal_move_policies() {
+    let player = SlowPlayer(Duration::from_millis(0));
+
+    let clumsy = Clumsy(Cell::new(2));
+    let mut game = Game::new(&clumsy, &player);
+    assert!(game.play_turn().is_err());
+    assert_eq!(game.get_current_score(), (2, 2));
+    assert_eq!(game.get_current_state(), Some(Side::Dark));
+    game.set_illegal_move_policy(IllegalMovePolicy::Retry(1));
+    game.play_turn().expect("Dark should get a second chance");
+    assert_eq!(game.get_current_state(), Some(Side::Light));
+
+    let clumsy = Clumsy(Cell::new(1));
+    let mut game = Game::new(&clumsy, &player);
+    game.set_illegal_move_policy(IllegalMovePolicy::Forfeit);
+    match game.play_turn() {
+        Err(ReversiError::IllegalMove(_)) => {}
+        _ => panic!("Dark's move should be illegal"),
+    }
+    assert_eq!(game.get_current_score(), (2, 2));
+    assert_eq!(game.get_result(), Some(GameResult::WinByForfeit(Side::Light)));
+}
+
+#[test]
+#[should_panic]
+fn test_strict_illegal_move_policy() {
+    let clumsy = Clumsy(Cell::new(1));
+    let mut game = Game::new(&clumsy, &clumsy);
+    game.set_illegal_move_policy(IllegalMovePolicy::Panic);
+    let _ = game.play_turn();
+}
+
+#[test]
+fn test_stateful_players() {
+    let mut dark = Recorder::default();
+    let mut light = Recorder::default();
+    {
+        let mut game = Game::new(&mut dark, &mut light);
+        while !game.is_endgame() {
+            game.play_turn().expect("The game is not over yet");
+        }
+        assert!(game.play_turn().is_err());
+    }
+    assert_eq!(dark.side, Some(Side::Dark));
+    assert_eq!(light.side, Some(Side::Light));
+    assert_eq!(dark.moves, light.opponent_moves);
+    assert_eq!(light.moves, dark.opponent_moves);
+    assert!(dark.result.is_some());
+    assert_eq!(dark.result, light.result);
+}
+
+#[test]
+fn test_pondering() {
+    let mut ponderer = Ponderer::default();
+    let opponent = SlowPlayer(Duration::from_millis(0));
+    {
+        let mut game = Game::new(&mut ponderer, &opponent);
+        game.set_pondering(true);
+        for _ in 0..6 {
+            game.play_turn().expect("The game is not over yet");
+        }
+    }
+    assert_eq!(ponderer.hits, 3);
+}
+
+/// A search running until it is told to stop, which then raises the given flag, if any.
+fn endless_search(stopped: Option<Arc<AtomicBool>>) -> impl FnOnce(Turn, &AtomicBool) -> u8 + Send + 'static {
+    move |_, stop| {
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        if let Some(stopped) = stopped {
+            stopped.store(true, Ordering::Relaxed);
+        }
+        42
+    }
+}
+
+#[test]
+fn test_ponder_stop() {
+    let turn = Turn::first_turn();
+    let ponder = Ponder::start(turn, endless_search(None));
+    assert_eq!(ponder.finish(&turn), Some(42));
+    let mut other_turn = turn;
+    other_turn.make_move(Coord::new(2, 3)).expect("Is this move illegal?");
+    let ponder = Ponder::start(turn, endless_search(None));
+    assert_eq!(ponder.finish(&other_turn), None);
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    drop(Ponder::start(turn, endless_search(Some(stopped.clone()))));
+    for _ in 0..1000 {
+        if stopped.load(Ordering::Relaxed) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(stopped.load(Ordering::Relaxed));
+}
+
+/// An asynchronous player which makes the game wait once before answering.
+struct Lazy;
+
+/// The future returned by `Lazy`.
+struct LazyMove(Turn, bool);
+
+impl Future for LazyMove {
+    type Output = Result<PlayerAction<()>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if self.1 {
+            Poll::Ready(SlowPlayer(Duration::from_millis(0)).make_move(&self.0))
+        } else {
+            self.1 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+impl IsAsyncPlayer<()> for Lazy {
+    fn make_move<'p>(&'p self, turn: Turn) -> PlayerFuture<'p, ()> {
+        Box::pin(LazyMove(turn, false))
+    }
+}
+
+/// Minimal executor, polling the future until it is ready.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_async_game() {
+    let mut game = Game::new(&Lazy, &Lazy);
+    while !game.is_endgame() {
+        block_on(game.play_turn_async()).expect("The game is not over yet");
+    }
+    assert!(game.get_result().is_some());
+    assert!(block_on(game.play_turn_async()).is_err());
+}
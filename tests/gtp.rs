@@ -0,0 +1,77 @@
+//! GTP front-end tests
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the front-end on the given commands and returns its responses.
+fn run_gtp(commands: &[&str]) -> Vec<String> {
+    let mut gtp = Command::new(env!("CARGO_BIN_EXE_reversi-gtp"))
+        .args(["--depth", "2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("The front-end can be started");
+    {
+        let input = gtp.stdin.as_mut().unwrap();
+        for command in commands {
+            writeln!(input, "{}", command).unwrap();
+        }
+    }
+    let output = gtp.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+        .split("\n\n")
+        .filter(|response| !response.is_empty())
+        .map(|response| response.to_string())
+        .collect()
+}
+
+#[test]
+fn test_gtp_commands() {
+    let responses = run_gtp(&[
+        "1 protocol_version",
+        "boardsize 10",
+        "boardsize 8",
+        "known_command genmove",
+        "play black f5",
+        "play black d6",
+        "genmove white",
+        "final_score",
+        "undo",
+        "showboard",
+        "play white pass",
+        "play black pass",
+        "foo",
+        "quit",
+        "name",
+    ]);
+    assert_eq!(responses.len(), 14);
+    assert_eq!(responses[0], "=1 2");
+    assert_eq!(responses[1], "? unacceptable size");
+    assert_eq!(responses[2], "=");
+    assert_eq!(responses[3], "= true");
+    assert_eq!(responses[4], "=");
+    assert_eq!(responses[5], "? illegal move");
+    assert!(["= d6", "= f6", "= f4"].contains(&responses[6].as_str()));
+    assert_eq!(responses[7], "= 0");
+    assert_eq!(responses[8], "=");
+    // Undoing as Black takes back both White's reply and Black's move.
+    assert!(responses[9].ends_with("black 2 - 2 white, black to move"));
+    assert_eq!(responses[10], "=");
+    assert_eq!(responses[11], "? illegal move");
+    assert_eq!(responses[12], "? unknown command");
+    assert_eq!(responses[13], "=");
+}
+
+#[test]
+fn test_gtp_final_score() {
+    // Dark wipes Light out after nine moves, and is awarded the empty squares.
+    let mut commands: Vec<String> = ["d3", "c3", "b3", "d2", "e1", "d6", "d7", "e3", "f4"].iter().enumerate()
+        .map(|(index, coord)| format!("play {} {}", if index % 2 == 0 { "black" } else { "white" }, coord))
+        .collect();
+    commands.push("final_score".to_string());
+    let commands: Vec<&str> = commands.iter().map(|command| command.as_str()).collect();
+    let responses = run_gtp(&commands);
+    assert_eq!(responses.len(), 10);
+    assert!(responses[..9].iter().all(|response| response == "="));
+    assert_eq!(responses[9], "= B+64");
+}
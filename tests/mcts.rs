@@ -0,0 +1,55 @@
+//! Monte Carlo Tree Search tests
+
+extern crate reversi;
+
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::mcts::*;
+use reversi::{Result, ReversiError, Side};
+
+/// A player which always plays the first legal move.
+struct Naive;
+
+impl IsPlayer<()> for Naive {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        turn.get_legal_moves().first().map(|&coord| PlayerAction::Move(coord)).ok_or(ReversiError::EndedGame(*turn))
+    }
+}
+
+#[test]
+fn test_mcts_beats_naive_player() {
+    for &playout in &[Playout::Random, Playout::Heuristic] {
+        let mut mcts = MctsPlayer::new(MctsLimit::Iterations(150));
+        mcts.set_playout(playout);
+        mcts.set_seed([1, 2, 3, 4]);
+        let mut game = Game::new(&mut mcts, &Naive);
+        while !game.is_endgame() {
+            game.play_turn().expect("The game is not over yet");
+        }
+        assert_eq!(game.get_result().and_then(|result| result.get_winner()), Some(Side::Dark));
+    }
+}
+
+#[test]
+fn test_mcts_search() {
+    let mut mcts = MctsPlayer::new(MctsLimit::Iterations(100));
+    let mut turn = Turn::first_turn();
+    let (coord, win_rate) = mcts.search(&turn).expect("The first turn has legal moves");
+    assert!(turn.check_move(coord).is_ok());
+    assert!((0.0..=1.0).contains(&win_rate));
+    turn.make_move(coord).expect("The move is legal");
+    let reply = turn.get_legal_moves()[0];
+    turn.make_move(reply).expect("The move is legal");
+    assert!(mcts.search(&turn).is_some());
+    let action: PlayerAction<()> = IsPlayer::make_move(&mcts, &turn).expect("The game is not over yet");
+    match action {
+        PlayerAction::Move(coord) => assert!(turn.check_move(coord).is_ok()),
+        _ => panic!("The player should move"),
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_mcts_without_iterations() {
+    let _ = MctsPlayer::new(MctsLimit::Iterations(0));
+}
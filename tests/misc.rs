@@ -34,3 +34,53 @@ fn test_second_turn() {
         }
     }
 }
+
+#[test]
+fn test_coord_notation() {
+    assert_eq!(Coord::new(4, 5).to_string(), "f5");
+    assert_eq!("F5".parse::<Coord>().unwrap(), Coord::new(4, 5));
+    for invalid in &["i1", "a9", "a0", "f", "5f", "f+5", "f05", "f5 "] {
+        assert!(invalid.parse::<Coord>().is_err(), "{} is not a coordinate", invalid);
+    }
+    assert_eq!(parse_coords("f5d6 c3").unwrap(), vec![Coord::new(4, 5), Coord::new(5, 3), Coord::new(2, 2)]);
+    assert!(parse_coords("f05").is_err());
+}
+
+#[test]
+fn test_turn_notation() {
+    let mut turn = Turn::first_turn();
+    let notation = format!("{}", turn);
+    assert_eq!(notation, "---------------------------OX------XO--------------------------- X");
+    assert_eq!(notation.parse::<Turn>().expect("Valid notation"), turn);
+    turn.make_move(Coord::new(2, 3)).expect("Is this move illegal?");
+    assert_eq!(format!("{}", turn).parse::<Turn>().expect("Valid notation"), turn);
+    assert!("---- X".parse::<Turn>().is_err());
+    assert!("---------------------------OX------XO--------------------------- -".parse::<Turn>().is_err());
+    let ended = format!("{} -", "X".repeat(NUM_CELLS));
+    assert!(ended.parse::<Turn>().expect("Valid notation").is_end_state());
+}
+
+#[test]
+fn test_symmetries() {
+    let coord = Coord::new(2, 3);
+    let mut turn = Turn::first_turn();
+    turn.make_move(coord).expect("Is this move illegal?");
+    let mut images = Vec::new();
+    for &symmetry in SYMMETRIES.iter() {
+        let image = turn.transform(symmetry);
+        assert_eq!(image.transform(symmetry.inverse()), turn);
+        assert_eq!(coord.transform(symmetry).transform(symmetry.inverse()), coord);
+        assert!(image.get_cell(coord.transform(symmetry)).expect("Within the board").is_some());
+        for &dir in DIRECTIONS.iter() {
+            assert_eq!(coord.step(dir).transform(symmetry), coord.transform(symmetry).step(dir.transform(symmetry)));
+        }
+        assert_eq!(image.canonical().0, turn.canonical().0);
+        if !images.contains(&image) {
+            images.push(image);
+        }
+    }
+    assert_eq!(images.len(), 8);
+    let (canonical, symmetry) = turn.canonical();
+    assert_eq!(turn.transform(symmetry), canonical);
+    assert!(images.iter().all(|image| canonical.get_board() <= image.get_board()));
+}
@@ -0,0 +1,86 @@
+//! NBoard protocol tests
+
+extern crate reversi;
+
+use reversi::board::*;
+use reversi::ggf::*;
+use reversi::Side;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const START: &str = "---------------------------O*------*O--------------------------- *";
+
+#[test]
+fn test_ggf() {
+    let game = parse_game(&format!("(;GM[Othello]PC[NBoard]PB[me]PW[you]TY[8]BO[8 {}]B[F5//1.2]W[d6];)", START)).unwrap();
+    assert_eq!(game.side, Side::Dark);
+    assert_eq!(game.moves, vec![Some(Coord::new(4, 5)), Some(Coord::new(5, 3))]);
+    assert_eq!(game.get_side_to_move(), Side::Dark);
+    assert_eq!(game.get_current_turn().unwrap().get_score(), (3, 3));
+    assert_eq!(parse_move("PA").unwrap(), None);
+    assert!(parse_game("(;BO[8 ---];)").is_err());
+    assert!(parse_game("(;B[F5").is_err());
+}
+
+#[test]
+fn test_nboard_engine() {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_reversi-nboard"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("The engine can be started");
+    {
+        let input = engine.stdin.as_mut().unwrap();
+        writeln!(input, "nboard 2").unwrap();
+        writeln!(input, "set depth 2").unwrap();
+        writeln!(input, "set game (;GM[Othello]BO[8 {}]B[F5];)", START).unwrap();
+        writeln!(input, "ping 1").unwrap();
+        writeln!(input, "hint 2").unwrap();
+        writeln!(input, "go").unwrap();
+        writeln!(input, "move PA").unwrap();
+        writeln!(input, "learn").unwrap();
+        writeln!(input, "quit").unwrap();
+    }
+    let output = engine.wait_with_output().unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "set myname reversi");
+    assert_eq!(lines[1], "pong 1");
+    assert_eq!(lines[2], "status Analyzing");
+    assert!(lines[3].starts_with("search ") && lines[4].starts_with("search "));
+    assert_eq!(lines[5], "status");
+    // White's replies to F5 are D6, F6 and F4.
+    assert!(["=== D6", "=== F6", "=== F4"].iter().any(|reply| lines[6].starts_with(reply)));
+    // Dark has legal moves, so it cannot pass.
+    assert_eq!(lines[7], "status Illegal move PA");
+    assert_eq!(lines[8], "learned");
+}
+
+#[test]
+fn test_nboard_errors() {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_reversi-nboard"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("The engine can be started");
+    {
+        let input = engine.stdin.as_mut().unwrap();
+        writeln!(input, "set depth 1").unwrap();
+        writeln!(input, "set game (;GM[Othello]BO[8 ---];)").unwrap();
+        writeln!(input, "set game (;GM[Othello]BO[8 {}]B[A1];)", START).unwrap();
+        writeln!(input, "move F5").unwrap();
+        writeln!(input, "move A1").unwrap();
+        writeln!(input, "move PA").unwrap();
+        writeln!(input, "go").unwrap();
+        writeln!(input, "quit").unwrap();
+    }
+    let output = engine.wait_with_output().unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "status Invalid game");
+    assert_eq!(lines[1], "status Illegal moves in game");
+    assert_eq!(lines[2], "status Illegal move A1");
+    assert_eq!(lines[3], "status Illegal move PA");
+    // Light is still to move after the rejected moves.
+    assert!(["=== D6", "=== F6", "=== F4"].iter().any(|reply| lines[4].starts_with(reply)));
+}
@@ -0,0 +1,76 @@
+//! Rating tests
+
+extern crate reversi;
+
+use reversi::game::*;
+use reversi::rating::*;
+use reversi::Side;
+use std::io::Cursor;
+
+#[test]
+fn test_elo() {
+    let mut ratings = Ratings::new();
+    let games = vec![RatedGame::new("Alice", "Bob", GameResult::WinByDisks(Side::Dark, (40, 24)))];
+    ratings.update_elo(&games, ScoreMode::Result);
+    assert_eq!(ratings.get("Alice").unwrap().elo, 1516.0);
+    assert_eq!(ratings.get("Bob").unwrap().elo, 1484.0);
+
+    let mut disk_ratings = Ratings::new();
+    disk_ratings.update_elo(&games, ScoreMode::Disks);
+    assert_eq!(disk_ratings.get("Alice").unwrap().elo, 1504.0);
+    // Results not decided by disks count as full wins.
+    disk_ratings.update_elo(&[RatedGame::new("Bob", "Alice", GameResult::WinByResignation(Side::Dark))], ScoreMode::Disks);
+    assert!(disk_ratings.get("Bob").unwrap().elo > 1500.0);
+}
+
+#[test]
+fn test_glicko() {
+    // The example from Glickman's description of the Glicko-2 system.
+    let mut ratings = Ratings::new();
+    let rating = |glicko, deviation| PlayerRating { elo: 1500.0, glicko, deviation, volatility: 0.06 };
+    ratings.set("Player", rating(1500.0, 200.0));
+    ratings.set("First", rating(1400.0, 30.0));
+    ratings.set("Second", rating(1550.0, 100.0));
+    ratings.set("Third", rating(1700.0, 300.0));
+    ratings.set("Idle", rating(1500.0, 50.0));
+    let games = vec![
+        RatedGame::new("Player", "First", GameResult::WinByDisks(Side::Dark, (33, 31))),
+        RatedGame::new("Second", "Player", GameResult::WinByTimeout(Side::Dark)),
+        RatedGame::new("Player", "Third", GameResult::WinByResignation(Side::Light)),
+    ];
+    ratings.update_glicko(&games, ScoreMode::Result);
+    let player = ratings.get("Player").unwrap();
+    assert!((player.glicko - 1464.06).abs() < 0.01);
+    assert!((player.deviation - 151.52).abs() < 0.01);
+    assert!((player.volatility - 0.05999).abs() < 0.00001);
+    assert!(ratings.get("Idle").unwrap().deviation > 50.0);
+}
+
+#[test]
+fn test_rating_files() {
+    let games = read_games(Cursor::new("# Club night\nAlice Smith\tBob\t40-24\nBob\tCarol\t32-32\n")).unwrap();
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].dark, "Alice Smith");
+    assert_eq!(games[1].result, GameResult::Draw);
+    assert!(read_games(Cursor::new("Alice\tBob\t40")).is_err());
+
+    let ggf = "(;GM[Othello]PB[Alice]PW[Bob]RE[+16.000]BO[8 ---------------------------O*------*O--------------------------- *];)
+        (;GM[Othello]PB[Bob]PW[Carol]RE[-2:r];)(;GM[Othello]PB[Carol]PW[Alice]B[D3]W[C3]B[B3]W[D2]B[E1]W[D6]B[D7]W[E3]B[F4];)";
+    let ggf_games = read_ggf_games(ggf).unwrap();
+    assert_eq!(ggf_games[0], RatedGame::new("Alice", "Bob", GameResult::WinByDisks(Side::Dark, (40, 24))));
+    assert_eq!(ggf_games[1].result, GameResult::WinByResignation(Side::Light));
+    assert_eq!(ggf_games[2].result, GameResult::WinByDisks(Side::Dark, (64, 0)));
+    assert!(read_ggf_games("(;PB[Alice]PW[Bob]B[F5];)").is_err());
+    assert!(read_ggf_games("(;PB[Alice]PW[Bob]RE[+99];)").is_err());
+
+    let mut ratings = Ratings::new();
+    ratings.update_elo(&games, ScoreMode::Result);
+    ratings.update_glicko(&games, ScoreMode::Result);
+    let mut bytes = Vec::new();
+    ratings.write_to(&mut bytes).unwrap();
+    let loaded = Ratings::read_from(Cursor::new(bytes)).unwrap();
+    assert_eq!(loaded.get_players(), ratings.get_players());
+    for line in &["a\tNaN\t1500\t350\t0.06", "a\t1500\tinf\t350\t0.06", "a\t1500\t1500\t0\t0.06", "a\t1500\t1500\t350\t-inf"] {
+        assert!(Ratings::read_from(Cursor::new(line.as_bytes())).is_err());
+    }
+}
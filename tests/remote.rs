@@ -0,0 +1,88 @@
+//! Remote player tests, on localhost
+
+extern crate reversi;
+
+use reversi::*;
+use reversi::game::*;
+use reversi::remote::*;
+use reversi::engine::Engine;
+use std::io::{BufRead, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// Returns a listener on a free local port.
+fn listen() -> TcpListener {
+    TcpListener::bind("127.0.0.1:0").unwrap()
+}
+
+#[test]
+fn test_remote_game() {
+    let listener = listen();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve_player::<(), _>(Engine::from_spec("search:1").unwrap(), stream)
+    });
+    let mut remote = RemotePlayer::connect(address, Duration::from_secs(5)).unwrap();
+    let mut local = Engine::from_spec("random").unwrap();
+    let mut game: Game<(), _, _> = Game::new(&mut remote, &mut local);
+    game.set_time_controls(clock::TimeControl::SuddenDeath(Duration::from_secs(60)), clock::TimeControl::SuddenDeath(Duration::from_secs(60)));
+    while !game.is_endgame() {
+        game.play_turn().unwrap();
+    }
+    assert!(matches!(game.get_result(), Some(GameResult::WinByDisks(..)) | Some(GameResult::Draw)));
+    drop(game);
+    // The remote side stops once notified about the end of the game.
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_remote_timeout() {
+    let listener = listen();
+    let address = listener.local_addr().unwrap();
+    let silent = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        // Reads the request and never answers.
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        thread::sleep(Duration::from_millis(500));
+        line
+    });
+    let remote = RemotePlayer::connect(address, Duration::from_millis(100)).unwrap();
+    let local = Engine::from_spec("random").unwrap();
+    let mut game: Game<(), _, _> = Game::new(&remote, local);
+    match game.play_turn() {
+        Err(ReversiError::PlayerUnavailable(Side::Dark)) => {}
+        _ => panic!("The remote player should be unavailable"),
+    }
+    assert_eq!(game.get_result(), Some(GameResult::WinByForfeit(Side::Light)));
+    assert!(silent.join().unwrap().starts_with("move ---------------------------OX------XO--------------------------- X"));
+}
+
+#[test]
+fn test_remote_disconnection() {
+    let listener = listen();
+    let address = listener.local_addr().unwrap();
+    let stream = TcpStream::connect(address).unwrap();
+    drop(listener.accept().unwrap());
+    let remote = RemotePlayer::new(stream, Duration::from_secs(5)).unwrap();
+    let local = Engine::from_spec("random").unwrap();
+    let mut game: Game<(), _, _> = Game::new(local, &remote);
+    game.play_turn().unwrap();
+    assert!(game.play_turn().is_err());
+    assert_eq!(game.get_result(), Some(GameResult::WinByForfeit(Side::Dark)));
+}
+
+#[test]
+fn test_remote_durations() {
+    for &(option, value) in &[("--time", "inf"), ("--time", "-1"), ("--timeout", "1e30"), ("--timeout", "NaN")] {
+        let output = Command::new(env!("CARGO_BIN_EXE_reversi-remote"))
+            .args(["host", "random", "random", option, value])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), format!("Invalid value for {}\n", option));
+    }
+}
@@ -0,0 +1,60 @@
+//! Self-play tests
+
+extern crate reversi;
+
+use reversi::board::*;
+use reversi::selfplay::*;
+use reversi::Side;
+use std::io::Cursor;
+
+#[test]
+fn test_reproducible_games() {
+    let play = |seed| {
+        let mut self_play = SelfPlay::new(Engine::from_spec("search:1").unwrap(), Engine::from_spec("mcts:50").unwrap());
+        self_play.set_noise_plies(4);
+        self_play.set_seed(seed);
+        self_play.play_game()
+    };
+    let games = [play([1, 2, 3, 4]), play([1, 2, 3, 4])];
+    assert!(games[0].len() >= 30);
+    assert_eq!(games[0].iter().map(|record| record.coord).collect::<Vec<Coord>>(),
+               games[1].iter().map(|record| record.coord).collect::<Vec<Coord>>());
+    let result = games[0][0].result;
+    for record in &games[0] {
+        assert!(record.turn.check_move(record.coord).is_ok());
+        assert_eq!(record.result, result);
+        match record.turn.get_state() {
+            Some(Side::Dark) => assert_eq!(record.unit, ScoreUnit::Disks),
+            _ => assert!(record.unit == ScoreUnit::Probability && (0.0..=1.0).contains(&record.score)),
+        }
+    }
+}
+
+#[test]
+fn test_record_formats() {
+    let mut self_play = SelfPlay::new(Engine::Random, Engine::Random);
+    self_play.set_seed([4, 3, 2, 1]);
+    let records = self_play.play_game();
+
+    let mut writer = RecordWriter::new(Vec::new(), RecordFormat::Binary).unwrap();
+    writer.write(&records).unwrap();
+    let bytes = writer.into_inner().unwrap();
+    let read = read_records(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(read.len(), records.len());
+    for (record, read) in records.iter().zip(&read) {
+        assert_eq!(read.turn, record.turn);
+        assert_eq!(read.coord, record.coord);
+        assert_eq!(read.unit, ScoreUnit::None);
+        assert_eq!(read.result, record.result);
+    }
+    assert!(read_records(&mut Cursor::new(&bytes[..bytes.len() - 1])).is_err());
+
+    let mut writer = RecordWriter::new(Vec::new(), RecordFormat::Csv).unwrap();
+    writer.write(&records).unwrap();
+    let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), records.len() + 1);
+    assert_eq!(lines[0], "board,side,move,score,unit,result");
+    assert!(lines[1].starts_with("---------------------------OX------XO---------------------------,X,"));
+    assert!(lines[1].contains(",0,none,"));
+}
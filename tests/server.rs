@@ -0,0 +1,196 @@
+//! HTTP server tests, on localhost
+
+extern crate reversi;
+
+use reversi::server::*;
+use reversi::websocket::*;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+/// Starts a server on a free local port and returns its address.
+fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut server = Server::new();
+    server.set_engine("search:2").unwrap();
+    thread::spawn(move || server.serve(listener));
+    address
+}
+
+/// Sends a request and returns the response's status and body.
+fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, path, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let body = response.split("\r\n\r\n").nth(1).unwrap().to_string();
+    (status, body)
+}
+
+#[test]
+fn test_server_api() {
+    let address = start_server();
+    let (status, body) = request(address, "POST", "/games", "");
+    assert_eq!(status, 201);
+    assert!(body.starts_with("{\"id\":1,\"board\":[\"........\",\"........\",\"........\",\"...OX...\""));
+    assert!(body.contains("\"side\":\"dark\",\"legal_moves\":[\"d3\",\"c4\",\"f5\",\"e6\"],\"score\":{\"dark\":2,\"light\":2}"));
+
+    let (status, body) = request(address, "POST", "/games/1/moves", "{\"move\": \"f5\"}");
+    assert_eq!(status, 200);
+    assert!(body.contains("\"side\":\"light\""));
+    assert!(body.contains("\"moves\":[\"f5\"],\"result\":null"));
+    assert_eq!(request(address, "POST", "/games/1/moves", "{\"move\": \"a1\"}").0, 409);
+    assert_eq!(request(address, "POST", "/games/1/moves", "f5").0, 400);
+
+    let (status, body) = request(address, "POST", "/games/1/engine-move", "");
+    assert_eq!(status, 200);
+    assert!(body.contains("\"side\":\"dark\""));
+    let (status, transcript) = request(address, "GET", "/games/1/transcript", "");
+    assert_eq!(status, 200);
+    assert!(transcript.starts_with("f5") && transcript.len() == 4);
+
+    assert_eq!(request(address, "POST", "/games/1/undo", "").0, 200);
+    let (status, body) = request(address, "GET", "/games/1", "");
+    assert_eq!(status, 200);
+    assert!(body.contains("\"moves\":[]"));
+    assert_eq!(request(address, "POST", "/games/1/undo", "").0, 409);
+
+    assert_eq!(request(address, "POST", "/games", "").0, 201);
+    assert_eq!(request(address, "GET", "/games/3", "").0, 404);
+    assert_eq!(request(address, "GET", "/players", "").0, 404);
+    assert_eq!(request(address, "POST", "/games/2/engine-move", "{\"engine\": \"alien\"}").0, 400);
+    assert_eq!(request(address, "POST", "/games/2/engine-move", "{\"engine\": \"search:30\"}").0, 400);
+    assert_eq!(request(address, "POST", "/games/2/engine-move", "{\"engine\": \"mcts:0\"}").0, 400);
+    assert_eq!(request(address, "POST", "/games/2/engine-move", "{\"engine\": \"search:1:/etc/passwd\"}").0, 400);
+    assert_eq!(request(address, "POST", "/games/2/engine-move", "{\"engine\": \"mcts:50\"}").0, 200);
+    assert_eq!(request(address, "GET", "/games/2", "").0, 200);
+}
+
+#[test]
+fn test_server_handle() {
+    let server = Server::new();
+    let response = server.handle(&Request::new("POST", "/games", ""));
+    assert_eq!(response.status, 201);
+    for _ in 0..60 {
+        if server.handle(&Request::new("POST", "/games/1/engine-move", "{\"engine\":\"random\"}")).status != 200 {
+            break;
+        }
+    }
+    let response = server.handle(&Request::new("GET", "/games/1", ""));
+    assert!(response.body.contains("\"side\":null,\"legal_moves\":[]"));
+    assert!(!response.body.contains("\"result\":null"));
+    assert_eq!(json_field("{\"a\": 1, \"move\" : \"c4\"}", "move"), Some("c4".to_string()));
+    assert_eq!(json_field("{\"engine\":\"\\\"move\\\"\"}", "engine"), Some("\"move\"".to_string()));
+    assert_eq!(json_field("{\"engine\":\"\\\"move\\\"\"}", "move"), None);
+    assert_eq!(json_field("{\"time\": 1e3, \"ok\": true}", "time"), Some("1e3".to_string()));
+    assert_eq!(json_field("{\"move\": \"c\\u0034\"}", "move"), Some("c4".to_string()));
+    for invalid in &["{\"move\": \"c4\"", "{\"move\": \"c4\"} x", "{\"move\": c4}", "{\"time\": inf}", "{\"a\": {\"move\": \"c4\"}}", "\"move\": \"c4\""] {
+        assert_eq!(json_object(invalid), None, "{} is not a flat object", invalid);
+    }
+    assert_eq!(json_object(" { } "), Some(Vec::new()));
+    assert_eq!(json_string("a\"b\n"), "\"a\\\"b\\n\"");
+}
+
+/// Connects to the events of a game and returns the handshake's response and the connection.
+fn connect_events(address: SocketAddr, id: u64) -> (String, BufReader<TcpStream>) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET /games/{}/events HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                    Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n", id).unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut response = String::new();
+    while !response.ends_with("\r\n\r\n") {
+        if reader.read_line(&mut response).unwrap() == 0 {
+            break;
+        }
+    }
+    (response, reader)
+}
+
+/// Reads the next event sent to a spectator.
+fn next_event(reader: &mut BufReader<TcpStream>) -> String {
+    let (opcode, payload) = read_frame(reader).unwrap();
+    assert_eq!(opcode, OPCODE_TEXT);
+    String::from_utf8(payload).unwrap()
+}
+
+#[test]
+fn test_server_events() {
+    let address = start_server();
+    assert_eq!(request(address, "POST", "/games", "{\"time\": 600, \"increment\": 1}").0, 201);
+    let (response, mut events) = connect_events(address, 1);
+    assert!(response.starts_with("HTTP/1.1 101 "));
+    assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+    assert!(next_event(&mut events).starts_with("{\"type\":\"state\",\"state\":{\"id\":1,"));
+    assert_eq!(connect_events(address, 2).0.split("\r\n").next(), Some("HTTP/1.1 404 Not Found"));
+    assert_eq!(request(address, "GET", "/games/1/events", "").0, 400);
+
+    request(address, "POST", "/games/1/moves", "{\"move\": \"f5\"}");
+    assert_eq!(next_event(&mut events),
+               "{\"type\":\"move\",\"side\":\"dark\",\"move\":\"f5\",\"flips\":[\"e5\"],\"score\":{\"dark\":4,\"light\":1}}");
+    let clock = next_event(&mut events);
+    assert!(clock.starts_with("{\"type\":\"clock\",\"dark\":60"));
+    assert!(clock.ends_with(",\"light\":600000}"));
+    request(address, "POST", "/games/1/moves", "{\"move\": \"d6\"}");
+    assert!(next_event(&mut events).contains("\"move\":\"d6\",\"flips\":[\"d5\"]"));
+    next_event(&mut events);
+    request(address, "POST", "/games/1/undo", "");
+    assert_eq!(next_event(&mut events), "{\"type\":\"undo\",\"moves\":[\"d6\",\"f5\"],\"side\":\"dark\"}");
+
+    while request(address, "POST", "/games/1/engine-move", "{\"engine\": \"random\"}").0 == 200 {}
+    let mut last = String::new();
+    while !last.contains("\"type\":\"end\"") {
+        last = next_event(&mut events);
+    }
+    assert!(last.contains("\"reason\":\"disks\""));
+    // The server closes the connection after the end of the game.
+    assert_eq!(read_frame(&mut events).unwrap().0, OPCODE_CLOSE);
+    write_frame(events.get_mut(), OPCODE_CLOSE, &[], Some([1, 2, 3, 4])).unwrap();
+    assert!(read_frame(&mut events).is_err());
+    let (_, mut events) = connect_events(address, 1);
+    assert!(next_event(&mut events).starts_with("{\"type\":\"state\""));
+    assert_eq!(read_frame(&mut events).unwrap().0, OPCODE_CLOSE);
+}
+
+#[test]
+fn test_server_spectator_close() {
+    let address = start_server();
+    request(address, "POST", "/games", "");
+    let (_, mut events) = connect_events(address, 1);
+    next_event(&mut events);
+    write_frame(events.get_mut(), OPCODE_PING, b"ping", Some([5, 6, 7, 8])).unwrap();
+    assert_eq!(read_frame(&mut events).unwrap(), (OPCODE_PONG, b"ping".to_vec()));
+    write_frame(events.get_mut(), OPCODE_CLOSE, &[], Some([5, 6, 7, 8])).unwrap();
+    assert_eq!(read_frame(&mut events).unwrap().0, OPCODE_CLOSE);
+    assert!(read_frame(&mut events).is_err());
+    // Moves are still played once the spectator has left.
+    assert_eq!(request(address, "POST", "/games/1/moves", "{\"move\": \"f5\"}").0, 200);
+}
+
+#[test]
+fn test_server_timeout() {
+    let server = Server::new();
+    server.handle(&Request::new("POST", "/games", "{\"time\": 0.05}"));
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/moves", "{\"move\": \"f5\"}")).status, 200);
+    thread::sleep(std::time::Duration::from_millis(100));
+    let response = server.handle(&Request::new("GET", "/games/1", ""));
+    assert!(response.body.contains("\"result\":\"dark\""));
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/moves", "{\"move\": \"d6\"}")).status, 409);
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/undo", "")).status, 409);
+    let response = server.handle(&Request::new("GET", "/games/1", ""));
+    assert!(response.body.contains("\"side\":\"light\""));
+    assert!(response.body.contains("\"moves\":[\"f5\"],\"result\":\"dark\""));
+    assert_eq!(server.handle(&Request::new("POST", "/games", "{\"time\": -1}")).status, 400);
+}
+
+#[test]
+fn test_server_invalid_time_controls() {
+    let server = Server::new();
+    for body in &["{\"time\": \"inf\"}", "{\"time\": 1e30}", "{\"time\": \"NaN\"}", "{\"time\": 60, \"increment\": \"inf\"}"] {
+        assert_eq!(server.handle(&Request::new("POST", "/games", body)).status, 400, "{} is rejected", body);
+    }
+    // The server still works after rejecting them.
+    assert_eq!(server.handle(&Request::new("POST", "/games", "{\"time\": 60}")).status, 201);
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/moves", "{\"move\": \"f5\"}")).status, 200);
+}
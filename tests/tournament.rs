@@ -0,0 +1,98 @@
+//! Tournament tests
+
+extern crate reversi;
+
+use reversi::turn::*;
+use reversi::game::*;
+use reversi::search::*;
+use reversi::eval::*;
+use reversi::tournament::*;
+use reversi::{Result, ReversiError, Side};
+use std::collections::HashSet;
+
+/// A player which always plays the first legal move.
+struct Naive;
+
+impl IsPlayer<()> for Naive {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        turn.get_legal_moves().first().map(|&coord| PlayerAction::Move(coord)).ok_or(ReversiError::EndedGame(*turn))
+    }
+}
+
+/// A player which always plays the legal move at the given index, modulo their number.
+struct Picky(usize);
+
+impl IsPlayer<()> for Picky {
+    fn make_move(&self, turn: &Turn) -> Result<PlayerAction<()>> {
+        let moves = turn.get_legal_moves();
+        match moves.len() {
+            0 => Err(ReversiError::EndedGame(*turn)),
+            len => Ok(PlayerAction::Move(moves[self.0 % len])),
+        }
+    }
+}
+
+fn names(count: usize) -> Vec<String> {
+    (0..count).map(|index| format!("Player {}", index + 1)).collect()
+}
+
+#[test]
+fn test_round_robin() {
+    let mut tournament = Tournament::new(names(5), TournamentSystem::RoundRobin);
+    assert_eq!(tournament.get_total_rounds(), 5);
+    let mut met = HashSet::new();
+    let mut darks = [0; 5];
+    while let Some(pairings) = tournament.pair_next_round() {
+        assert_eq!(pairings.len(), 2);
+        for pairing in pairings {
+            assert!(met.insert((pairing.dark.min(pairing.light), pairing.dark.max(pairing.light))));
+            darks[pairing.dark] += 1;
+            assert!(tournament.record_result(pairing, GameResult::WinByDisks(Side::Dark, (40, 24)), (40, 24)));
+            assert!(!tournament.record_result(pairing, GameResult::Draw, (32, 32)));
+        }
+    }
+    assert!(tournament.is_over());
+    assert_eq!(met.len(), 10);
+    assert!(darks.iter().all(|&count| count == 2));
+    // Every player won as Dark twice and had one bye.
+    for standing in tournament.get_standings() {
+        assert_eq!(standing.points, 3.0);
+        assert_eq!(standing.disks, 2 * 40 + 2 * 24 + 32);
+    }
+}
+
+#[test]
+fn test_swiss() {
+    let strong = SearchPlayer::new(DiskDifference, 2);
+    let players: [&dyn IsPlayer<()>; 4] = [&Naive, &strong, &Naive, &Naive];
+    let mut tournament = Tournament::new(names(4), TournamentSystem::Swiss(3));
+    tournament.pair_next_round().expect("The first round can be paired");
+    assert!(tournament.pair_next_round().is_none());
+    tournament.play(&players);
+    assert!(tournament.is_over());
+    assert_eq!(tournament.get_paired_rounds(), 3);
+    let standings = tournament.get_standings();
+    assert_eq!(standings.iter().map(|standing| standing.points).sum::<f64>(), 6.0);
+    assert!(standings.windows(2).all(|pair| pair[0].points >= pair[1].points));
+
+    let crosstable = tournament.get_crosstable();
+    let lines: Vec<&str> = crosstable.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].contains("R3") && lines[0].contains("BQ"));
+    assert!(lines[1].contains(&tournament.get_names()[standings[0].player]));
+}
+
+#[test]
+fn test_swiss_rematches() {
+    // There are more rounds than opponents, so players have to meet again.
+    for &(count, rounds) in &[(4, 6), (16, 20)] {
+        let pickies: Vec<Picky> = (0..count).map(Picky).collect();
+        let players: Vec<&dyn IsPlayer<()>> = pickies.iter().map(|player| player as &dyn IsPlayer<()>).collect();
+        let mut tournament = Tournament::new(names(count), TournamentSystem::Swiss(rounds));
+        tournament.pair_next_round().expect("The first round can be paired");
+        tournament.play(&players);
+        assert!(tournament.is_over());
+        assert_eq!(tournament.get_paired_rounds(), rounds);
+        assert_eq!(tournament.get_standings().iter().map(|standing| standing.points).sum::<f64>(), (count / 2 * rounds) as f64);
+    }
+}
@@ -0,0 +1,55 @@
+//! Training tests
+
+extern crate reversi;
+
+use reversi::turn::*;
+use reversi::eval::*;
+use reversi::train::*;
+use std::io::Cursor;
+use std::process::Command;
+
+/// Builds a dataset of positions from a game, scored by their disk difference.
+fn disk_difference_samples() -> Vec<Sample> {
+    let mut samples = Vec::new();
+    let mut turn = Turn::first_turn();
+    while !turn.is_end_state() {
+        samples.push(Sample { turn, target: turn.get_score_diff() as f32 });
+        let moves = turn.get_legal_moves();
+        turn.make_move(moves[moves.len() / 2]).expect("The move is legal");
+    }
+    samples
+}
+
+#[test]
+fn test_read_dataset() {
+    let turn = Turn::first_turn();
+    let text = format!("# A comment\n{} 2.5\n\n{} -4\n", turn, turn);
+    let samples = read_dataset(Cursor::new(text)).expect("The dataset is valid");
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].turn, turn);
+    assert_eq!(samples[0].target, 2.5);
+    assert_eq!(samples[1].target, -4.0);
+    assert!(read_dataset(Cursor::new(format!("{}\n", turn))).is_err());
+}
+
+#[test]
+fn test_training_reduces_error() {
+    let samples = disk_difference_samples();
+    let mut evaluator = PatternEvaluator::new(2);
+    let initial_error = mean_squared_error(&evaluator, &samples);
+    let mut trainer = Trainer::new();
+    trainer.set_epochs(20);
+    let errors = trainer.train(&mut evaluator, &samples);
+    assert_eq!(errors.len(), 20);
+    assert!(mean_squared_error(&evaluator, &samples) < initial_error / 2.0);
+}
+
+#[test]
+fn test_train_options() {
+    let output = Command::new(env!("CARGO_BIN_EXE_reversi-train"))
+        .args(["dataset", "weights", "--stages", "4", "--init", "initial"])
+        .output()
+        .expect("The trainer can be started");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("--stages cannot be used with --init"));
+}
@@ -0,0 +1,105 @@
+//! Full-screen terminal interface tests, with keys piped instead of typed
+
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the interface has to show what a test expects.
+const DEADLINE: Duration = Duration::from_secs(60);
+
+/// Runs the interface with the given arguments on the given keys and returns its output.
+fn run_tui(args: &[&str], keys: &str) -> String {
+    let mut tui = Command::new(env!("CARGO_BIN_EXE_reversi-tui"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("The interface can be started");
+    tui.stdin.as_mut().unwrap().write_all(keys.as_bytes()).unwrap();
+    String::from_utf8(tui.wait_with_output().unwrap().stdout).unwrap()
+}
+
+/// Runs the interface with the given arguments on the given keys, until it shows the expected text,
+/// then quits it and returns its output. It panics if the text is not shown before the deadline.
+fn run_tui_until(args: &[&str], keys: &str, expected: &str) -> String {
+    let mut tui = Command::new(env!("CARGO_BIN_EXE_reversi-tui"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("The interface can be started");
+    tui.stdin.as_mut().unwrap().write_all(keys.as_bytes()).unwrap();
+    // The output is read on its own thread, so that waiting for it can time out.
+    let mut stdout = tui.stdout.take().unwrap();
+    let (sender, chunks) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 4096];
+        while let Ok(read) = stdout.read(&mut buffer) {
+            if read == 0 || sender.send(buffer[..read].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    let deadline = Instant::now() + DEADLINE;
+    let mut output = Vec::new();
+    while !String::from_utf8_lossy(&output).contains(expected) {
+        match chunks.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(chunk) => output.extend(chunk),
+            Err(_) => {
+                let _ = tui.kill();
+                panic!("The interface did not show {:?} in time", expected);
+            }
+        }
+    }
+    tui.stdin.as_mut().unwrap().write_all(b"q").unwrap();
+    output.extend(chunks.iter().flatten());
+    assert!(tui.wait().unwrap().success());
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_tui_keys() {
+    // The cursor starts on d3: Dark plays it, then Light plays c5 (left once, down twice).
+    let output = run_tui(&["--dark", "human", "--light", "human", "--time", "5+2"], "\r\x1b[Djj \rq");
+    assert!(output.contains("Light to move"));
+    assert!(output.contains(" 1. d3 c5"));
+    assert!(output.contains("Illegal move c5"));
+    assert!(output.contains("05:0"));
+    assert!(output.ends_with("\x1b[?25h\x1b[?1049l"));
+}
+
+#[test]
+fn test_tui_review() {
+    let path = env::temp_dir().join("reversi_test_tui_review.txt");
+    fs::write(&path, "f5d6c3").unwrap();
+    // Positions are evaluated in the background: the evaluation shows up on a later redraw.
+    let output = run_tui_until(&["--dark", "human", "--light", "human", "--load", path.to_str().unwrap()], "]]]][u]", "Eval: ");
+    fs::remove_file(&path).unwrap();
+    assert!(output.contains(" 1. f5 d6"));
+    assert!(output.contains(" 2. c3"));
+    assert!(output.contains("This is the last move"));
+    assert!(output.ends_with("\x1b[?25h\x1b[?1049l"));
+}
+
+#[test]
+fn test_tui_engines() {
+    // Engines search in the background, and keys keep being read meanwhile.
+    let output = run_tui_until(&["--dark", "random", "--light", "easy"], "x", "Game over: ");
+    assert!(output.ends_with("\x1b[?25h\x1b[?1049l"));
+    // Quitting does not wait for the end of a long search.
+    let output = run_tui_until(&["--dark", "mcts:1000000000", "--light", "human"], "", "Dark to move");
+    assert!(output.ends_with("\x1b[?25h\x1b[?1049l"));
+}
+
+#[test]
+fn test_tui_time_controls() {
+    for time in &["inf", "-1", "0", "1e30", "5+inf", "5+-1"] {
+        let tui = Command::new(env!("CARGO_BIN_EXE_reversi-tui")).args(["--time", time]).stdin(Stdio::null()).output().unwrap();
+        assert!(!tui.status.success());
+        assert_eq!(String::from_utf8(tui.stderr).unwrap(), "Invalid time control\n", "{} is rejected", time);
+    }
+}